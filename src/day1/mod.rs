@@ -1,6 +1,8 @@
 use crate::utils;
 use itertools::Itertools;
-use std::ops::Add;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
 fn number_of_inc_from_iter<I>(it: I) -> usize
 where
@@ -14,21 +16,460 @@ fn number_of_inc<T: Ord>(data: &Vec<T>) -> usize {
     number_of_inc_from_iter(data.iter())
 }
 
-fn number_of_inc_sliding_window3<T>(data: &Vec<T>) -> usize
+// keeps only the last n elements, the window sums share all but one element so
+// comparing the incoming element with the outgoing one is enough
+fn number_of_inc_window<I, T>(it: I, n: usize) -> usize
 where
-    T: Ord + Add<Output = T> + Clone,
+    I: IntoIterator<Item = T>,
+    T: PartialOrd,
 {
-    number_of_inc_from_iter(
-        data.iter()
-            .tuple_windows()
-            .map(|(a, b, c)| a.clone() + b.clone() + c.clone()),
-    )
+    assert!(n > 0, "Window size must be positive");
+    let mut it = it.into_iter();
+    let mut window: VecDeque<T> = it.by_ref().take(n).collect();
+    if window.len() < n {
+        return 0;
+    }
+    let mut count = 0;
+    it.for_each(|x| {
+        if x > window.pop_front().unwrap() {
+            count += 1;
+        }
+        window.push_back(x);
+    });
+    count
+}
+
+fn number_of_inc_sliding_window3<T>(data: &[T]) -> usize
+where
+    T: Copy + PartialOrd,
+{
+    number_of_inc_window(data.iter().copied(), 3)
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Detector {
+    // smallest step counted as an increase
+    min_delta: usize,
+    // after an increase, the depth has to fall this far below its peak
+    // before the next increase is counted
+    hysteresis: usize,
+    // depths are replaced by the median of the last `median_of` depths
+    median_of: usize,
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Detector {
+            min_delta: 1,
+            hysteresis: 0,
+            median_of: 1,
+        }
+    }
+}
+
+fn median_filter<I>(it: I, k: usize) -> impl Iterator<Item = usize>
+where
+    I: IntoIterator<Item = usize>,
+{
+    assert!(k > 0, "Median window must be positive");
+    let mut window = VecDeque::with_capacity(k);
+    it.into_iter().map(move |x| {
+        if window.len() == k {
+            window.pop_front();
+        }
+        window.push_back(x);
+        let sorted = window.iter().sorted().collect_vec();
+        *sorted[(sorted.len() - 1) / 2]
+    })
+}
+
+fn number_of_inc_detected<I>(it: I, detector: &Detector) -> usize
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut count = 0;
+    let mut armed = true;
+    let mut peak = 0;
+    median_filter(it, detector.median_of)
+        .tuple_windows()
+        .for_each(|(prev, x)| {
+            peak = peak.max(x);
+            if !armed && peak - x >= detector.hysteresis {
+                armed = true;
+            }
+            if armed && x >= prev + detector.min_delta {
+                count += 1;
+                armed = false;
+                peak = x;
+            }
+        });
+    count
 }
 
 fn read_file_into_vector(path: &str) -> Vec<usize> {
     utils::read_file_into_vector(path, |l| l.parse().unwrap())
 }
 
+fn read_file_into_iter(path: &str) -> impl Iterator<Item = usize> {
+    BufReader::new(File::open(path).expect("input data missing"))
+        .lines()
+        .map(|l| l.expect("read error").parse().unwrap())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Sample {
+    timestamp: u64,
+    depth: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Gap {
+    // timestamp of the last sample before the gap
+    after: u64,
+    duration: u64,
+}
+
+fn read_csv_into_iter(path: &str) -> impl Iterator<Item = Sample> {
+    BufReader::new(File::open(path).expect("input data missing"))
+        .lines()
+        .map(|l| l.expect("read error"))
+        .filter(|l| !l.starts_with("timestamp"))
+        .map(|l| {
+            let (timestamp, depth) = l.split_once(',').expect("malformed line");
+            Sample {
+                timestamp: timestamp.trim().parse().expect("timestamp not a u64"),
+                depth: depth.trim().parse().expect("depth not a usize"),
+            }
+        })
+}
+
+fn find_gaps<I>(it: I, max_interval: u64) -> Vec<Gap>
+where
+    I: Iterator<Item = Sample>,
+{
+    it.tuple_windows()
+        .filter_map(|(a, b)| {
+            assert!(a.timestamp <= b.timestamp, "samples are not sorted");
+            let duration = b.timestamp - a.timestamp;
+            if duration > max_interval {
+                Some(Gap {
+                    after: a.timestamp,
+                    duration,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// mean depth of every non-empty `span` long interval counted from the first sample
+struct TimeWindowIterator<I: Iterator<Item = Sample>> {
+    it: std::iter::Peekable<I>,
+    span: u64,
+    start: Option<u64>,
+}
+
+impl<I: Iterator<Item = Sample>> Iterator for TimeWindowIterator<I> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = *self.it.peek()?;
+        let start = *self.start.get_or_insert(first.timestamp);
+        assert!(first.timestamp >= start, "samples are not sorted");
+        let end = first.timestamp - (first.timestamp - start) % self.span + self.span;
+        let mut sum = 0;
+        let mut count = 0;
+        while let Some(sample) = self.it.next_if(|s| s.timestamp < end) {
+            sum += sample.depth;
+            count += 1;
+        }
+        Some(sum as f64 / count as f64)
+    }
+}
+
+fn time_windows<I>(it: I, span: u64) -> TimeWindowIterator<I>
+where
+    I: Iterator<Item = Sample>,
+{
+    assert!(span > 0, "Window span must be positive");
+    TimeWindowIterator {
+        it: it.peekable(),
+        span,
+        start: None,
+    }
+}
+
+fn number_of_inc_time_window<I>(it: I, span: u64, n: usize) -> usize
+where
+    I: Iterator<Item = Sample>,
+{
+    number_of_inc_window(time_windows(it, span), n)
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct DepthDrop {
+    // index of the element after the drop
+    position: usize,
+    depth: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct WindowStats {
+    // index of the first depth in the window
+    start: usize,
+    min: usize,
+    max: usize,
+    mean: f64,
+}
+
+// window statistics that attain an extreme, ties keep the first window
+#[derive(Debug, Default, PartialEq)]
+struct WindowSummary {
+    windows: usize,
+    lowest_min: Option<WindowStats>,
+    highest_max: Option<WindowStats>,
+    lowest_mean: Option<WindowStats>,
+    highest_mean: Option<WindowStats>,
+    // mean of all window means
+    mean: f64,
+}
+
+impl WindowSummary {
+    fn add(&mut self, stats: WindowStats) {
+        let replace = |current: &mut Option<WindowStats>,
+                       better: fn(&WindowStats, &WindowStats) -> bool| {
+            if current.map(|c| better(&stats, &c)).unwrap_or(true) {
+                *current = Some(stats);
+            }
+        };
+        replace(&mut self.lowest_min, |s, c| s.min < c.min);
+        replace(&mut self.highest_max, |s, c| s.max > c.max);
+        replace(&mut self.lowest_mean, |s, c| s.mean < c.mean);
+        replace(&mut self.highest_mean, |s, c| s.mean > c.mean);
+        self.windows += 1;
+        self.mean += (stats.mean - self.mean) / self.windows as f64;
+    }
+}
+
+#[derive(Debug, Default)]
+struct DepthReport {
+    increases: usize,
+    longest_increasing: Run,
+    longest_decreasing: Run,
+    // only the longest plateau is located, `plateaus()` streams every position
+    plateaus: usize,
+    longest_plateau: Option<Run>,
+    largest_drop: Option<DepthDrop>,
+    windows: WindowSummary,
+}
+
+// pops elements from the back that can no longer be the window extreme
+fn push_monotonic<F>(deque: &mut VecDeque<(usize, usize)>, i: usize, x: usize, dominates: F)
+where
+    F: Fn(usize, usize) -> bool,
+{
+    while let Some(&(_, back)) = deque.back() {
+        if dominates(x, back) {
+            deque.pop_back();
+        } else {
+            break;
+        }
+    }
+    deque.push_back((i, x));
+}
+
+// min and max come from monotonic deques, memory is bounded by the window size
+struct SlidingWindow {
+    size: usize,
+    mins: VecDeque<(usize, usize)>,
+    maxs: VecDeque<(usize, usize)>,
+    values: VecDeque<usize>,
+    sum: usize,
+    index: usize,
+}
+
+impl SlidingWindow {
+    fn new(size: usize) -> SlidingWindow {
+        assert!(size > 0, "Window size must be positive");
+        SlidingWindow {
+            size,
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+            values: VecDeque::with_capacity(size + 1),
+            sum: 0,
+            index: 0,
+        }
+    }
+
+    // returns the statistics once the window is full
+    fn push(&mut self, x: usize) -> Option<WindowStats> {
+        let i = self.index;
+        self.index += 1;
+        push_monotonic(&mut self.mins, i, x, |x, back| x <= back);
+        push_monotonic(&mut self.maxs, i, x, |x, back| x >= back);
+        self.values.push_back(x);
+        self.sum += x;
+        if self.values.len() > self.size {
+            self.sum -= self.values.pop_front().unwrap();
+        }
+        if i + 1 < self.size {
+            return None;
+        }
+        let start = i + 1 - self.size;
+        if self.mins[0].0 < start {
+            self.mins.pop_front();
+        }
+        if self.maxs[0].0 < start {
+            self.maxs.pop_front();
+        }
+        Some(WindowStats {
+            start,
+            min: self.mins[0].1,
+            max: self.maxs[0].1,
+            mean: self.sum as f64 / self.size as f64,
+        })
+    }
+}
+
+struct WindowStatsIterator<I: Iterator<Item = usize>> {
+    it: I,
+    window: SlidingWindow,
+}
+
+impl<I: Iterator<Item = usize>> Iterator for WindowStatsIterator<I> {
+    type Item = WindowStats;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self.it.next()?;
+            if let Some(stats) = self.window.push(x) {
+                return Some(stats);
+            }
+        }
+    }
+}
+
+fn window_stats<I>(it: I, size: usize) -> WindowStatsIterator<I>
+where
+    I: Iterator<Item = usize>,
+{
+    WindowStatsIterator {
+        it,
+        window: SlidingWindow::new(size),
+    }
+}
+
+// every run of at least two equal depths, yielded once the run ends
+struct PlateauIterator<I: Iterator<Item = usize>> {
+    it: std::iter::Enumerate<I>,
+    current: Option<(usize, Run)>,
+}
+
+impl<I: Iterator<Item = usize>> Iterator for PlateauIterator<I> {
+    type Item = Run;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.it.next(), self.current) {
+                (Some((_, x)), Some((p, mut run))) if p == x => {
+                    run.len += 1;
+                    self.current = Some((x, run));
+                }
+                (Some((i, x)), current) => {
+                    self.current = Some((x, Run { start: i, len: 1 }));
+                    if let Some((_, run)) = current.filter(|(_, r)| r.len > 1) {
+                        return Some(run);
+                    }
+                }
+                (None, current) => {
+                    self.current = None;
+                    return current.map(|(_, r)| r).filter(|r| r.len > 1);
+                }
+            }
+        }
+    }
+}
+
+fn plateaus<I>(it: I) -> PlateauIterator<I>
+where
+    I: Iterator<Item = usize>,
+{
+    PlateauIterator {
+        it: it.enumerate(),
+        current: None,
+    }
+}
+
+impl DepthReport {
+    fn from_depths<I>(it: I, window: usize) -> DepthReport
+    where
+        I: Iterator<Item = usize>,
+    {
+        let mut report = DepthReport::default();
+        let mut increasing = Run::default();
+        let mut decreasing = Run::default();
+        let mut plateau = Run::default();
+        let mut sliding = SlidingWindow::new(window);
+        let mut prev = None;
+        it.enumerate().for_each(|(i, x)| {
+            match prev {
+                Some(p) if p < x => {
+                    report.increases += 1;
+                    increasing.len += 1;
+                    decreasing = Run { start: i, len: 1 };
+                    plateau = Run { start: i, len: 1 };
+                }
+                Some(p) if p > x => {
+                    let drop = DepthDrop {
+                        position: i,
+                        depth: p - x,
+                    };
+                    if report.largest_drop.map(|d| d.depth).unwrap_or(0) < drop.depth {
+                        report.largest_drop = Some(drop);
+                    }
+                    decreasing.len += 1;
+                    increasing = Run { start: i, len: 1 };
+                    plateau = Run { start: i, len: 1 };
+                }
+                Some(_) => {
+                    plateau.len += 1;
+                    if plateau.len == 2 {
+                        report.plateaus += 1;
+                    }
+                    if report.longest_plateau.map(|r| r.len).unwrap_or(0) < plateau.len {
+                        report.longest_plateau = Some(plateau);
+                    }
+                    increasing = Run { start: i, len: 1 };
+                    decreasing = Run { start: i, len: 1 };
+                }
+                None => {
+                    increasing = Run { start: i, len: 1 };
+                    decreasing = Run { start: i, len: 1 };
+                    plateau = Run { start: i, len: 1 };
+                }
+            }
+            if increasing.len > report.longest_increasing.len {
+                report.longest_increasing = increasing;
+            }
+            if decreasing.len > report.longest_decreasing.len {
+                report.longest_decreasing = decreasing;
+            }
+            prev = Some(x);
+            if let Some(stats) = sliding.push(x) {
+                report.windows.add(stats);
+            }
+        });
+        report
+    }
+}
+
 #[test]
 fn task1_example() {
     let values = read_file_into_vector("src/day1/example.txt");
@@ -74,3 +515,183 @@ fn task2_puzzle_bench(b: &mut test::Bencher) {
         task2_puzzle();
     });
 }
+
+#[test]
+fn window_example() {
+    let values = read_file_into_vector("src/day1/example.txt");
+    assert_eq!(number_of_inc_window(values.iter().copied(), 1), 7);
+    assert_eq!(number_of_inc_window(values.iter().copied(), 3), 5);
+    assert_eq!(number_of_inc_window(values.iter().copied(), 10), 0);
+    assert_eq!(number_of_inc_window(values.iter().copied(), 11), 0);
+}
+
+#[test]
+fn window_puzzle_streaming() {
+    let result = number_of_inc_window(read_file_into_iter("src/day1/puzzle.txt"), 1);
+    assert_eq!(result, 1475);
+    let result = number_of_inc_window(read_file_into_iter("src/day1/puzzle.txt"), 3);
+    assert_eq!(result, 1516);
+}
+
+#[test]
+fn report_example() {
+    let values = read_file_into_vector("src/day1/example.txt");
+    let report = DepthReport::from_depths(values.iter().copied(), 3);
+    assert_eq!(report.increases, 7);
+    assert_eq!(report.longest_increasing, Run { start: 0, len: 4 });
+    assert_eq!(report.longest_decreasing, Run { start: 3, len: 2 });
+    assert_eq!(report.plateaus, 0);
+    assert_eq!(report.longest_plateau, None);
+    assert_eq!(
+        report.largest_drop,
+        Some(DepthDrop {
+            position: 4,
+            depth: 10
+        })
+    );
+    let stats = window_stats(values.into_iter(), 3).collect_vec();
+    assert_eq!(
+        stats.iter().map(|s| s.min).collect_vec(),
+        vec![199, 200, 200, 200, 200, 207, 240, 260]
+    );
+    assert_eq!(
+        stats.iter().map(|s| s.max).collect_vec(),
+        vec![208, 210, 210, 210, 240, 269, 269, 269]
+    );
+    assert_eq!(
+        stats.iter().map(|s| s.mean * 3.0).collect_vec(),
+        vec![607.0, 618.0, 618.0, 617.0, 647.0, 716.0, 769.0, 792.0]
+    );
+    let windows = &report.windows;
+    assert_eq!(windows.windows, 8);
+    assert_eq!(windows.lowest_min, Some(stats[0]));
+    assert_eq!(windows.highest_max, Some(stats[5]));
+    assert_eq!(windows.lowest_mean, Some(stats[0]));
+    assert_eq!(windows.highest_mean, Some(stats[7]));
+    assert!((windows.mean - 5384.0 / 24.0).abs() < 1e-9);
+}
+
+#[test]
+fn report_plateaus() {
+    let values = vec![3, 3, 3, 1, 2, 2, 5, 4, 4];
+    let report = DepthReport::from_depths(values.iter().copied(), 2);
+    assert_eq!(report.plateaus, 3);
+    assert_eq!(report.longest_plateau, Some(Run { start: 0, len: 3 }));
+    assert_eq!(
+        plateaus(values.iter().copied()).collect_vec(),
+        vec![
+            Run { start: 0, len: 3 },
+            Run { start: 4, len: 2 },
+            Run { start: 7, len: 2 }
+        ]
+    );
+    assert_eq!(
+        plateaus(vec![1, 2, 2, 3].into_iter()).collect_vec(),
+        vec![Run { start: 1, len: 2 }]
+    );
+    assert_eq!(plateaus(vec![1, 2, 3].into_iter()).count(), 0);
+    assert_eq!(report.longest_increasing, Run { start: 3, len: 2 });
+    assert_eq!(report.longest_decreasing, Run { start: 2, len: 2 });
+    let stats = window_stats(values.into_iter(), 2).collect_vec();
+    assert_eq!(
+        stats.iter().map(|s| s.min).collect_vec(),
+        vec![3, 3, 1, 1, 2, 2, 4, 4]
+    );
+    assert_eq!(
+        stats.iter().map(|s| s.max).collect_vec(),
+        vec![3, 3, 3, 2, 2, 5, 5, 4]
+    );
+    assert_eq!(report.windows.lowest_min.map(|s| s.start), Some(2));
+    assert_eq!(report.windows.highest_max.map(|s| s.start), Some(5));
+}
+
+#[test]
+fn report_puzzle_streaming() {
+    let report = DepthReport::from_depths(read_file_into_iter("src/day1/puzzle.txt"), 3);
+    assert_eq!(report.increases, 1475);
+    assert_eq!(report.windows.windows, 1998);
+    let located = plateaus(read_file_into_iter("src/day1/puzzle.txt")).collect_vec();
+    assert_eq!(located.len(), report.plateaus);
+    assert_eq!(
+        located.iter().max_by_key(|r| r.len).copied(),
+        report.longest_plateau
+    );
+    let windows = window_stats(read_file_into_iter("src/day1/puzzle.txt"), 3).count();
+    assert_eq!(windows, 1998);
+}
+
+#[test]
+fn detector_default() {
+    let detector = Detector::default();
+    let values = read_file_into_vector("src/day1/example.txt");
+    assert_eq!(number_of_inc_detected(values, &detector), 7);
+    let values = read_file_into_vector("src/day1/puzzle.txt");
+    assert_eq!(number_of_inc_detected(values, &detector), 1475);
+}
+
+#[test]
+fn detector_thresholds() {
+    let values = vec![100, 101, 105, 104, 110, 109, 111, 130];
+    let detector = Detector {
+        min_delta: 3,
+        ..Default::default()
+    };
+    assert_eq!(number_of_inc_detected(values.clone(), &detector), 3);
+    let detector = Detector {
+        hysteresis: 2,
+        ..Default::default()
+    };
+    assert_eq!(number_of_inc_detected(values.clone(), &detector), 1);
+    let detector = Detector {
+        hysteresis: 1,
+        ..Default::default()
+    };
+    assert_eq!(number_of_inc_detected(values, &detector), 3);
+}
+
+#[test]
+fn detector_median() {
+    let values = vec![10, 10, 50, 10, 11];
+    assert_eq!(
+        median_filter(values.clone(), 3).collect_vec(),
+        [10, 10, 10, 10, 11]
+    );
+    let detector = Detector {
+        median_of: 3,
+        ..Default::default()
+    };
+    assert_eq!(number_of_inc_detected(values, &detector), 1);
+}
+
+#[test]
+fn csv_gaps() {
+    let gaps = find_gaps(read_csv_into_iter("src/day1/example.csv"), 5);
+    assert_eq!(
+        gaps,
+        vec![Gap {
+            after: 6,
+            duration: 14
+        }]
+    );
+}
+
+#[test]
+fn csv_time_windows() {
+    let means = time_windows(read_csv_into_iter("src/day1/example.csv"), 5).collect_vec();
+    assert_eq!(means, vec![204.25, 203.5, 258.0]);
+    let result = number_of_inc_time_window(read_csv_into_iter("src/day1/example.csv"), 5, 1);
+    assert_eq!(result, 1);
+    let result = number_of_inc_time_window(read_csv_into_iter("src/day1/example.csv"), 1, 1);
+    assert_eq!(result, 7);
+    let result = number_of_inc_time_window(read_csv_into_iter("src/day1/example.csv"), 1, 3);
+    assert_eq!(result, 5);
+}
+
+#[test]
+fn window_float_means() {
+    // every window of two sums to 0.8, rounding must not count increases
+    let values = (0..100_000).map(|i| if i % 2 == 0 { 0.1 } else { 0.7 });
+    assert_eq!(number_of_inc_window(values, 2), 0);
+    let values = (0..100_000).map(|i| if i % 2 == 0 { 0.1 } else { 0.7 });
+    assert_eq!(number_of_inc_window(values, 1), 50_000);
+}
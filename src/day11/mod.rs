@@ -1,3 +1,4 @@
+use crate::utils;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::convert::TryInto;
@@ -24,17 +25,10 @@ const GRID_SIZE: usize = 10;
 type Grid = [[Cell; GRID_SIZE]; GRID_SIZE];
 
 fn read_file_into_grid(path: &str) -> Grid {
-    std::fs::read_to_string(path)
-        .expect("Input data missing")
-        .lines()
-        .map(|x| {
-            let line: [Cell; 10] = x
-                .chars()
-                .map(|c| Cell::Energy(c.to_digit(10).unwrap() as u8))
-                .collect_vec()
-                .as_slice()
-                .try_into()
-                .unwrap();
+    utils::read_file_into_grid(path, |c| Cell::Energy(c.to_digit(10).unwrap() as u8))
+        .into_iter()
+        .map(|line| {
+            let line: [Cell; GRID_SIZE] = line.as_slice().try_into().unwrap();
             line
         })
         .collect_vec()
@@ -64,11 +64,7 @@ impl<'a> Iterator for NeighborCoordinatesIterator<'a> {
 }
 
 fn read_file(path: &str) -> Grid {
-    let points = utils::read_file_into_vector(path, |l| {
-        l.chars()
-            .map(|x| x.to_digit(10).unwrap() as Cost)
-            .collect_vec()
-    });
+    let points = utils::read_file_into_grid(path, |x| x.to_digit(10).unwrap() as Cost);
     let width = points.get(0).unwrap().len() as i16;
     let height = points.len() as i16;
     Grid {
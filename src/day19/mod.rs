@@ -0,0 +1,231 @@
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, Sub};
+
+const MIN_OVERLAP: usize = 12;
+const MIN_COMMON_DISTANCES: usize = MIN_OVERLAP * (MIN_OVERLAP - 1) / 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct Point {
+    x: isize,
+    y: isize,
+    z: isize,
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl Point {
+    fn manhattan(&self, other: &Point) -> usize {
+        let d = *self - *other;
+        d.x.unsigned_abs() + d.y.unsigned_abs() + d.z.unsigned_abs()
+    }
+
+    fn distance_squared(&self, other: &Point) -> isize {
+        let d = *self - *other;
+        d.x * d.x + d.y * d.y + d.z * d.z
+    }
+
+    // 6 facings of the x axis times 4 rotations around it
+    fn rotate(&self, rotation: u8) -> Point {
+        let Point { x, y, z } = *self;
+        let (x, y, z) = match rotation / 4 {
+            0 => (x, y, z),
+            1 => (-x, -y, z),
+            2 => (y, -x, z),
+            3 => (-y, x, z),
+            4 => (z, y, -x),
+            5 => (-z, y, x),
+            _ => panic!("Wrong rotation"),
+        };
+        match rotation % 4 {
+            0 => Point { x, y, z },
+            1 => Point { x, y: -z, z: y },
+            2 => Point { x, y: -y, z: -z },
+            _ => Point { x, y: z, z: -y },
+        }
+    }
+}
+
+struct Scanner {
+    beacons: Vec<Point>,
+    distances: HashSet<isize>,
+}
+
+impl Scanner {
+    fn new(beacons: Vec<Point>) -> Scanner {
+        let distances = beacons
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| a.distance_squared(b))
+            .collect();
+        Scanner { beacons, distances }
+    }
+
+    // returns the rotation and position of `other` relative to `self`
+    fn align(&self, other: &Scanner) -> Option<(u8, Point)> {
+        if self.distances.intersection(&other.distances).count() < MIN_COMMON_DISTANCES {
+            return None;
+        }
+        (0..24).find_map(|rotation| {
+            let mut offsets = HashMap::new();
+            self.beacons
+                .iter()
+                .cartesian_product(other.beacons.iter())
+                .for_each(|(a, b)| *offsets.entry(*a - b.rotate(rotation)).or_insert(0) += 1);
+            offsets
+                .into_iter()
+                .find(|&(_, count)| count >= MIN_OVERLAP)
+                .map(|(offset, _)| (rotation, offset))
+        })
+    }
+
+    fn transform(&mut self, rotation: u8, offset: Point) {
+        self.beacons
+            .iter_mut()
+            .for_each(|b| *b = b.rotate(rotation) + offset);
+    }
+}
+
+fn read_file(path: &str) -> Vec<Scanner> {
+    let input = std::fs::read_to_string(path).expect("Missing input data");
+    let lines = input.lines().collect_vec();
+    lines
+        .split(|&s| s.is_empty())
+        .map(|block| {
+            Scanner::new(
+                block
+                    .iter()
+                    .skip(1)
+                    .map(|&l| {
+                        let mut coords = l.split(',').map(|x| x.parse::<isize>().unwrap());
+                        Point {
+                            x: coords.next().unwrap(),
+                            y: coords.next().unwrap(),
+                            z: coords.next().unwrap(),
+                        }
+                    })
+                    .collect_vec(),
+            )
+        })
+        .collect_vec()
+}
+
+// aligns every scanner to the coordinate system of the first one,
+// returns the aligned scanners and their positions
+fn align_all(scanners: Vec<Scanner>) -> (Vec<Scanner>, Vec<Point>) {
+    let mut unaligned = scanners;
+    let mut aligned = vec![unaligned.remove(0)];
+    let mut positions = vec![Point { x: 0, y: 0, z: 0 }];
+    let mut checked = 0;
+    while !unaligned.is_empty() {
+        assert!(checked < aligned.len(), "Scanners cannot be aligned");
+        let reference = checked;
+        checked += 1;
+        let mut i = 0;
+        while i < unaligned.len() {
+            if let Some((rotation, offset)) = aligned[reference].align(&unaligned[i]) {
+                let mut scanner = unaligned.swap_remove(i);
+                scanner.transform(rotation, offset);
+                aligned.push(scanner);
+                positions.push(offset);
+            } else {
+                i += 1;
+            }
+        }
+    }
+    (aligned, positions)
+}
+
+fn count_beacons(scanners: Vec<Scanner>) -> usize {
+    let (aligned, _) = align_all(scanners);
+    aligned
+        .iter()
+        .flat_map(|s| s.beacons.iter())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn max_distance(scanners: Vec<Scanner>) -> usize {
+    let (_, positions) = align_all(scanners);
+    positions
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| a.manhattan(b))
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn rotations_test() {
+    let p = Point { x: 1, y: 2, z: 3 };
+    let rotated: HashSet<Point> = (0..24).map(|r| p.rotate(r)).collect();
+    assert_eq!(rotated.len(), 24);
+}
+
+#[test]
+fn task1_example() {
+    let scanners = read_file("src/day19/example.txt");
+    let result = count_beacons(scanners);
+    println!("D19T1E {}", result);
+    assert_eq!(result, 79);
+}
+
+#[test]
+fn task1_puzzle() {
+    let scanners = read_file("src/day19/input.txt");
+    let result = count_beacons(scanners);
+    println!("D19T1P {}", result);
+    assert_eq!(result, 648);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_example() {
+    let scanners = read_file("src/day19/example.txt");
+    let result = max_distance(scanners);
+    println!("D19T2E {}", result);
+    assert_eq!(result, 3621);
+}
+
+#[test]
+fn task2_puzzle() {
+    let scanners = read_file("src/day19/input.txt");
+    let result = max_distance(scanners);
+    println!("D19T2P {}", result);
+    assert_eq!(result, 12851);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
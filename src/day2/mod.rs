@@ -1,50 +1,677 @@
+use crate::day9;
 use crate::utils;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Move {
     Forward(isize),
     Up(isize),
     Down(isize),
+    // degrees, positive is clockwise
+    Turn(isize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Direction {
+    Forward,
+    Up,
+    Down,
+    TurnLeft,
+    TurnRight,
+}
+
+impl Direction {
+    fn parse(s: &str) -> Option<Direction> {
+        match s {
+            "forward" => Some(Direction::Forward),
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "turn left" => Some(Direction::TurnLeft),
+            "turn right" => Some(Direction::TurnRight),
+            _ => None,
+        }
+    }
+
+    fn to_move(self, x: isize) -> Move {
+        match self {
+            Direction::Forward => Move::Forward(x),
+            Direction::Up => Move::Up(x),
+            Direction::Down => Move::Down(x),
+            Direction::TurnLeft => Move::Turn(-x),
+            Direction::TurnRight => Move::Turn(x),
+        }
+    }
 }
 
 fn read_file_into_vector(path: &str) -> Vec<Move> {
     utils::read_file_into_vector(path, |l| {
-        let (direction, x) = l.split_once(' ').expect("malformed line");
+        let (d, x) = l.rsplit_once(' ').expect("malformed line");
         let x = x.parse::<isize>().expect("x not a isize");
-        match direction {
-            "forward" => Move::Forward(x),
-            "up" => Move::Up(x),
-            "down" => Move::Down(x),
-            _ => panic!("unexpected direction"),
-        }
+        Direction::parse(d)
+            .expect("unexpected direction")
+            .to_move(x)
     })
 }
 
-fn move_sum(v: &Vec<Move>) -> (isize, isize) {
-    let mut ret = (0, 0);
-    v.iter().for_each(|m| match m {
-        Move::Forward(x) => ret.0 += x,
-        Move::Up(x) => ret.1 -= x,
-        Move::Down(x) => ret.1 += x,
-    });
-    ret
+#[derive(Debug, PartialEq)]
+struct ScriptError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+type Result<T> = std::result::Result<T, ScriptError>;
+
+fn error<T>(line: usize, message: String) -> Result<T> {
+    Err(ScriptError { line, message })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Literal(isize),
+    Constant(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Move(Direction, Value),
+    Repeat(Value, Vec<Statement>),
+    Call(String),
+}
+
+#[derive(Debug, Default)]
+struct Script {
+    constants: HashMap<String, isize>,
+    macros: HashMap<String, Vec<Statement>>,
+    body: Vec<Statement>,
+}
+
+fn is_name(s: &str) -> bool {
+    matches!(s.chars().next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Script {
+    fn parse(s: &str) -> Result<Script> {
+        let mut script = Script::default();
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim()))
+            .filter(|(_, l)| !l.is_empty());
+        script.body = script.parse_block(&mut lines, None)?;
+        Ok(script)
+    }
+
+    fn parse_value(&self, line: usize, s: &str) -> Result<Value> {
+        if let Ok(x) = s.parse::<isize>() {
+            Ok(Value::Literal(x))
+        } else if self.constants.contains_key(s) {
+            Ok(Value::Constant(s.to_string()))
+        } else {
+            error(line, format!("unknown constant {}", s))
+        }
+    }
+
+    fn value(&self, value: &Value) -> isize {
+        match value {
+            Value::Literal(x) => *x,
+            Value::Constant(name) => self.constants[name],
+        }
+    }
+
+    // `opening` is the line of the `{` that started the block
+    fn parse_block<'a, I>(
+        &mut self,
+        lines: &mut I,
+        opening: Option<usize>,
+    ) -> Result<Vec<Statement>>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+    {
+        let mut block = vec![];
+        while let Some((line, l)) = lines.next() {
+            let tokens = l.split_whitespace().collect::<Vec<_>>();
+            match tokens[..] {
+                ["}"] => {
+                    return match opening {
+                        Some(_) => Ok(block),
+                        None => error(line, "unmatched }".to_string()),
+                    }
+                }
+                ["const", name, "=", value] => {
+                    if !is_name(name) {
+                        return error(line, format!("invalid constant name {}", name));
+                    }
+                    if self.constants.contains_key(name) {
+                        return error(line, format!("constant {} already defined", name));
+                    }
+                    let value = self.parse_value(line, value)?;
+                    let value = self.value(&value);
+                    self.constants.insert(name.to_string(), value);
+                }
+                ["macro", name, "{"] => {
+                    if opening.is_some() {
+                        return error(line, "macros must be defined at top level".to_string());
+                    }
+                    if !is_name(name) || Direction::parse(name).is_some() {
+                        return error(line, format!("invalid macro name {}", name));
+                    }
+                    if self.macros.contains_key(name) {
+                        return error(line, format!("macro {} already defined", name));
+                    }
+                    let body = self.parse_block(lines, Some(line))?;
+                    self.macros.insert(name.to_string(), body);
+                }
+                ["repeat", count, "{"] => {
+                    let count = self.parse_value(line, count)?;
+                    if self.value(&count) < 0 {
+                        return error(line, "negative repeat count".to_string());
+                    }
+                    let body = self.parse_block(lines, Some(line))?;
+                    block.push(Statement::Repeat(count, body));
+                }
+                ["turn", side, x] => match Direction::parse(&format!("turn {}", side)) {
+                    Some(d) => block.push(Statement::Move(d, self.parse_value(line, x)?)),
+                    None => return error(line, format!("unexpected turn {}", side)),
+                },
+                [d, x] => match Direction::parse(d) {
+                    Some(d) => block.push(Statement::Move(d, self.parse_value(line, x)?)),
+                    None => return error(line, format!("unexpected direction {}", d)),
+                },
+                [name] => {
+                    if !self.macros.contains_key(name) {
+                        return error(line, format!("unknown macro {}", name));
+                    }
+                    block.push(Statement::Call(name.to_string()));
+                }
+                _ => return error(line, format!("malformed statement {}", l)),
+            }
+        }
+        match opening {
+            Some(line) => error(line, "unclosed block".to_string()),
+            None => Ok(block),
+        }
+    }
+
+    fn expand_block(&self, block: &[Statement], moves: &mut Vec<Move>) {
+        block.iter().for_each(|s| match s {
+            Statement::Move(d, x) => moves.push(d.to_move(self.value(x))),
+            Statement::Repeat(count, body) => {
+                (0..self.value(count)).for_each(|_| self.expand_block(body, moves))
+            }
+            Statement::Call(name) => self.expand_block(&self.macros[name], moves),
+        });
+    }
+
+    fn expand(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        self.expand_block(&self.body, &mut moves);
+        moves
+    }
+}
+
+fn read_script_into_vector(path: &str) -> Result<Vec<Move>> {
+    let input = std::fs::read_to_string(path).expect("input data missing");
+    Ok(Script::parse(&input)?.expand())
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct SubmarineState {
+    horizontal: isize,
+    // perpendicular to the initial heading, only the heading model moves there
+    lateral: isize,
+    depth: isize,
+    aim: isize,
+    // degrees clockwise from the initial heading, in 0..360
+    heading: isize,
+    // what the heading model lost by rounding horizontal and lateral
+    drift: (f64, f64),
+}
+
+trait NavigationModel {
+    fn apply(&self, state: &mut SubmarineState, m: &Move);
+
+    fn navigate(&self, v: &[Move]) -> SubmarineState {
+        let mut state = SubmarineState::default();
+        v.iter().for_each(|m| self.apply(&mut state, m));
+        state
+    }
+
+    fn trajectory(&self, v: &[Move]) -> Trajectory {
+        let mut state = SubmarineState::default();
+        let mut states = Vec::with_capacity(v.len() + 1);
+        states.push(state);
+        v.iter().for_each(|m| {
+            self.apply(&mut state, m);
+            states.push(state);
+        });
+        Trajectory { states }
+    }
+}
+
+// states[0] is the start, states[i + 1] is the state after command i
+struct Trajectory {
+    states: Vec<SubmarineState>,
+}
+
+impl Trajectory {
+    fn max_depth(&self) -> isize {
+        self.states.iter().map(|s| s.depth).max().unwrap()
+    }
+
+    // index of the first command after which depth is greater than `depth`
+    fn first_deeper_than(&self, depth: isize) -> Option<usize> {
+        self.states.iter().skip(1).position(|s| s.depth > depth)
+    }
+
+    fn distance(&self) -> f64 {
+        self.states
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| {
+                let dh = (b.horizontal - a.horizontal) as f64;
+                let dl = (b.lateral - a.lateral) as f64;
+                let dd = (b.depth - a.depth) as f64;
+                (dh * dh + dl * dl + dd * dd).sqrt()
+            })
+            .sum()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("index,horizontal,lateral,depth,aim,heading\n");
+        self.states.iter().enumerate().for_each(|(i, s)| {
+            csv += &format!(
+                "{},{},{},{},{},{}\n",
+                i, s.horizontal, s.lateral, s.depth, s.aim, s.heading
+            )
+        });
+        csv
+    }
+
+    // side view, x is horizontal and y is depth
+    fn to_svg(&self) -> String {
+        let (min_x, max_x) = self
+            .states
+            .iter()
+            .map(|s| s.horizontal)
+            .minmax()
+            .into_option()
+            .unwrap();
+        let (min_y, max_y) = self
+            .states
+            .iter()
+            .map(|s| s.depth)
+            .minmax()
+            .into_option()
+            .unwrap();
+        let points = self
+            .states
+            .iter()
+            .map(|s| format!("{},{}", s.horizontal, s.depth))
+            .join(" ");
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n\
+             <polyline fill=\"none\" stroke=\"black\" points=\"{}\"/>\n\
+             </svg>\n",
+            min_x,
+            min_y,
+            (max_x - min_x).max(1),
+            (max_y - min_y).max(1),
+            points
+        )
+    }
+}
+
+// up and down change depth directly, turns are ignored
+struct DirectModel;
+
+// up and down change aim, forward dives along it, turns are ignored
+struct AimModel;
+
+// like `AimModel`, but forward follows the heading, the position is rounded
+// to whole units and the rounding is carried over to the next forward move
+struct HeadingModel;
+
+// exact for the axis directions so they never drift
+fn unit_vector(heading: isize) -> (f64, f64) {
+    match heading {
+        0 => (1.0, 0.0),
+        90 => (0.0, 1.0),
+        180 => (-1.0, 0.0),
+        270 => (0.0, -1.0),
+        _ => {
+            let (sin, cos) = (heading as f64).to_radians().sin_cos();
+            (cos, sin)
+        }
+    }
+}
+
+impl NavigationModel for DirectModel {
+    fn apply(&self, state: &mut SubmarineState, m: &Move) {
+        match m {
+            Move::Forward(x) => state.horizontal += x,
+            Move::Up(x) => state.depth -= x,
+            Move::Down(x) => state.depth += x,
+            Move::Turn(_) => (),
+        }
+    }
+}
+
+impl NavigationModel for AimModel {
+    fn apply(&self, state: &mut SubmarineState, m: &Move) {
+        match m {
+            Move::Forward(x) => {
+                state.horizontal += x;
+                state.depth += x * state.aim;
+            }
+            Move::Up(x) => state.aim -= x,
+            Move::Down(x) => state.aim += x,
+            Move::Turn(_) => (),
+        }
+    }
+}
+
+impl NavigationModel for HeadingModel {
+    fn apply(&self, state: &mut SubmarineState, m: &Move) {
+        match m {
+            Move::Forward(x) => {
+                let (dh, dl) = unit_vector(state.heading);
+                let h = state.horizontal as f64 + state.drift.0 + *x as f64 * dh;
+                let l = state.lateral as f64 + state.drift.1 + *x as f64 * dl;
+                state.horizontal = h.round() as isize;
+                state.lateral = l.round() as isize;
+                state.drift = (h - h.round(), l - l.round());
+                state.depth += x * state.aim;
+            }
+            Move::Up(x) => state.aim -= x,
+            Move::Down(x) => state.aim += x,
+            Move::Turn(x) => state.heading = (state.heading + x).rem_euclid(360),
+        }
+    }
+}
+
+fn move_sum(v: &[Move]) -> SubmarineState {
+    DirectModel.navigate(v)
+}
+
+fn move_sum2(v: &[Move]) -> SubmarineState {
+    AimModel.navigate(v)
+}
+
+fn answer(v: SubmarineState) -> isize {
+    v.horizontal * v.depth
+}
+
+// largest magnitude of a single command, `None` is unlimited
+#[derive(Debug, Copy, Clone, Default)]
+struct Limits {
+    forward: Option<isize>,
+    aim: Option<isize>,
+    // longest acceptable program, also bounds the search with a limited aim
+    commands: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Unreachable;
+
+// A program is fully described by the aim changes and how far forward the
+// submarine still moves after each of them: every aim change `c` with `s`
+// forward remaining adds `c * s` to the final depth. Breakpoints are the
+// distinct `s` values with the total aim change made there.
+type Breakpoints = Vec<(isize, isize)>;
+
+struct Planner {
+    forward: isize,
+    aim: isize,
+    budget: usize,
+    failed: HashSet<(isize, isize, usize)>,
 }
 
-fn move_sum2(v: &Vec<Move>) -> (isize, isize) {
-    let mut ret = (0, 0);
-    let mut aim = 0;
-    v.iter().for_each(|m| match m {
-        Move::Forward(x) => {
-            ret.0 += x;
-            ret.1 += x * aim;
+fn div_ceil(a: isize, b: isize) -> usize {
+    (a as usize).div_ceil(b as usize)
+}
+
+impl Planner {
+    fn segment(&self, g: isize) -> usize {
+        div_ceil(g, self.forward)
+    }
+
+    // tries to spend at most `budget` commands, `prev` is the previous
+    // breakpoint or the whole horizontal distance before the first one
+    fn search(&mut self, prev: isize, depth: isize, spent: usize, out: &mut Breakpoints) -> bool {
+        if depth == 0 {
+            return spent + self.segment(prev) <= self.budget;
+        }
+        if self.failed.contains(&(prev, depth, spent)) {
+            return false;
+        }
+        let first = out.is_empty();
+        let highest = if first { prev } else { prev - 1 };
+        for s in (1..=highest).rev() {
+            let base = spent + self.segment(prev - s) + self.segment(s);
+            if base >= self.budget {
+                continue;
+            }
+            let budget = (self.budget - base) as isize;
+            let reach = budget.saturating_mul(self.aim);
+            if depth.abs() > reach.saturating_mul(s) {
+                continue;
+            }
+            // what is left after this breakpoint must fit below it
+            let rest = (budget - 1).saturating_mul(self.aim).saturating_mul(s - 1);
+            let lo = (-rest.saturating_sub(depth).div_euclid(s)).max(-reach);
+            let hi = depth.saturating_add(rest).div_euclid(s).min(reach);
+            for c in (lo..=hi).filter(|&c| c != 0) {
+                let spent = spent + self.segment(prev - s) + div_ceil(c.abs(), self.aim);
+                if spent + self.segment(s) > self.budget {
+                    continue;
+                }
+                let remaining = depth - c * s;
+                if remaining != 0 {
+                    // the next breakpoint needs at least one more aim command
+                    let left = (self.budget - spent - self.segment(s)) as isize;
+                    let reach = left.saturating_mul(self.aim).saturating_mul(s - 1);
+                    if s < 2 || left == 0 || remaining.abs() > reach {
+                        continue;
+                    }
+                }
+                out.push((s, c));
+                if self.search(s, remaining, spent, out) {
+                    return true;
+                }
+                out.pop();
+            }
         }
-        Move::Up(x) => aim -= x,
-        Move::Down(x) => aim += x,
+        self.failed.insert((prev, depth, spent));
+        false
+    }
+}
+
+// divisors of a positive `n` in pairs from a walk up to its square root
+fn divisors(n: isize) -> impl Iterator<Item = isize> {
+    (1..)
+        .take_while(move |&i| i <= n / i)
+        .filter(move |&i| n % i == 0)
+        .flat_map(move |i| if i == n / i { vec![i] } else { vec![i, n / i] })
+}
+
+fn push_chunks(moves: &mut Vec<Move>, total: isize, limit: isize, m: fn(isize) -> Move) {
+    let mut left = total;
+    while left > 0 {
+        let x = left.min(limit);
+        moves.push(m(x));
+        left -= x;
+    }
+}
+
+// shortest program reaching (horizontal, depth) under the aim semantics of `move_sum2`.
+// With a limited aim this is an exhaustive search that grows exponentially with
+// the program length: targets the size of the puzzle answer with small limits
+// take tens of seconds, so set `commands` to give up on longer programs.
+fn plan(
+    horizontal: isize,
+    depth: isize,
+    limits: Limits,
+) -> std::result::Result<Vec<Move>, Unreachable> {
+    if horizontal < 0 || (horizontal == 0 && depth != 0) {
+        return Err(Unreachable);
+    }
+    let forward = limits.forward.unwrap_or(horizontal).max(0);
+    if horizontal > 0 && forward == 0 || depth != 0 && limits.aim.unwrap_or(1) <= 0 {
+        return Err(Unreachable);
+    }
+    let forward = forward.max(1);
+    let breakpoints = match limits.aim {
+        _ if depth == 0 => vec![],
+        // every aim change is a single command, one breakpoint is enough
+        None => {
+            let s = divisors(depth.abs())
+                .filter(|&s| s <= horizontal)
+                .min_by_key(|&s| (div_ceil(horizontal - s, forward) + div_ceil(s, forward), s))
+                .unwrap();
+            vec![(s, depth / s)]
+        }
+        Some(aim) => {
+            // a single aim change never needs to exceed the depth
+            let aim = aim.min(depth.abs());
+            let mut planner = Planner {
+                forward,
+                aim,
+                budget: div_ceil(horizontal, forward)
+                    + div_ceil(depth.abs(), aim.saturating_mul(horizontal)),
+                failed: HashSet::new(),
+            };
+            let mut out = vec![];
+            loop {
+                if limits.commands.unwrap_or(usize::MAX) < planner.budget {
+                    return Err(Unreachable);
+                }
+                if planner.search(horizontal, depth, 0, &mut out) {
+                    break;
+                }
+                planner.budget += 1;
+                planner.failed.clear();
+            }
+            out
+        }
+    };
+    let aim = limits.aim.unwrap_or(isize::MAX);
+    let mut moves = vec![];
+    let mut prev = horizontal;
+    breakpoints.iter().for_each(|&(s, c)| {
+        push_chunks(&mut moves, prev - s, forward, Move::Forward);
+        if c > 0 {
+            push_chunks(&mut moves, c, aim, Move::Down);
+        } else {
+            push_chunks(&mut moves, -c, aim, Move::Up);
+        }
+        prev = s;
     });
-    ret
+    push_chunks(&mut moves, prev, forward, Move::Forward);
+    if limits.commands.unwrap_or(usize::MAX) < moves.len() {
+        return Err(Unreachable);
+    }
+    Ok(moves)
+}
+
+// seabed depth is the map digit times `scale`, every cell is `cell_size` wide
+struct Seabed {
+    grid: day9::Grid,
+    cell_size: isize,
+    scale: isize,
+}
+
+impl Seabed {
+    fn depth_at(&self, horizontal: isize, lateral: isize) -> Option<isize> {
+        let x = horizontal.div_euclid(self.cell_size);
+        let y = lateral.div_euclid(self.cell_size);
+        if x > i16::MAX as isize || y > i16::MAX as isize || x < 0 || y < 0 {
+            return None;
+        }
+        self.grid
+            .point(x as i16, y as i16)
+            .map(|d| d as isize * self.scale)
+    }
 }
 
-fn answer(v: (isize, isize)) -> isize {
-    v.0 * v.1
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Contact {
+    // index of the command being executed
+    command: usize,
+    horizontal: isize,
+    depth: isize,
+    clearance: isize,
+}
+
+#[derive(Debug, Default)]
+struct ReplayReport {
+    collision: Option<Contact>,
+    min_clearance: Option<Contact>,
+    near_misses: Vec<Contact>,
+}
+
+// forward moves are replayed one unit at a time so the seabed is sampled
+// under every horizontal position, commands that leave the position unchanged
+// are not sampled again and the replay stops at the first collision
+fn replay<M: NavigationModel>(
+    model: &M,
+    v: &[Move],
+    seabed: &Seabed,
+    near_miss: isize,
+) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let mut state = SubmarineState::default();
+    let sample = |command: usize, state: &SubmarineState, report: &mut ReplayReport| {
+        let seabed = seabed.depth_at(state.horizontal, state.lateral)?;
+        let contact = Contact {
+            command,
+            horizontal: state.horizontal,
+            depth: state.depth,
+            clearance: seabed - state.depth,
+        };
+        if !matches!(report.min_clearance, Some(c) if c.clearance <= contact.clearance) {
+            report.min_clearance = Some(contact);
+        }
+        if contact.clearance <= 0 {
+            report.collision = Some(contact);
+        } else if contact.clearance <= near_miss {
+            report.near_misses.push(contact);
+        }
+        report.collision
+    };
+    for (i, m) in v.iter().enumerate() {
+        let (step, count) = match *m {
+            Move::Forward(x) => (Move::Forward(x.signum()), x.abs()),
+            _ => (*m, 1),
+        };
+        for _ in 0..count {
+            let position = (state.horizontal, state.lateral, state.depth);
+            model.apply(&mut state, &step);
+            if position == (state.horizontal, state.lateral, state.depth) {
+                continue;
+            }
+            if sample(i, &state, &mut report).is_some() {
+                return report;
+            }
+        }
+    }
+    report
+}
+
+fn read_seabed(path: &str, cell_size: isize, scale: isize) -> Seabed {
+    Seabed {
+        grid: day9::read_file_into_grid(path),
+        cell_size,
+        scale,
+    }
 }
 
 #[test]
@@ -92,3 +719,294 @@ fn task2_puzzle_bench(b: &mut test::Bencher) {
         task2_puzzle();
     });
 }
+
+#[test]
+fn script_example() {
+    let values = read_script_into_vector("src/day2/example_script.txt").unwrap();
+    assert_eq!(values, read_file_into_vector("src/day2/example.txt"));
+    assert_eq!(answer(move_sum(&values)), 150);
+    assert_eq!(answer(move_sum2(&values)), 900);
+}
+
+#[test]
+fn script_repeat() {
+    let script =
+        Script::parse("const N = 2\nrepeat N {\n  forward 1\n  repeat 2 {\n    down N\n  }\n}\n")
+            .unwrap();
+    assert_eq!(
+        script.expand(),
+        vec![
+            Move::Forward(1),
+            Move::Down(2),
+            Move::Down(2),
+            Move::Forward(1),
+            Move::Down(2),
+            Move::Down(2),
+        ]
+    );
+}
+
+#[test]
+fn script_errors() {
+    let line = |s: &str| Script::parse(s).unwrap_err().line;
+    assert_eq!(line("forward 1\nsideways 2"), 2);
+    assert_eq!(line("forward 1\n\n# comment\nup X"), 4);
+    assert_eq!(line("forward 1\nrepeat 2 {\nforward 1"), 2);
+    assert_eq!(line("forward 1\n}"), 2);
+    assert_eq!(line("dive\nmacro dive {\n}"), 1);
+    assert_eq!(line("repeat 2 {\nmacro dive {\n}\n}"), 2);
+    assert_eq!(line("const A = 1\nconst A = 2"), 2);
+    assert_eq!(line("turn left 45 degrees"), 1);
+}
+
+#[test]
+fn heading_model() {
+    let values = read_file_into_vector("src/day2/input.txt");
+    assert_eq!(HeadingModel.navigate(&values), AimModel.navigate(&values));
+    let values = read_file_into_vector("src/day2/example_turns.txt");
+    let state = HeadingModel.navigate(&values);
+    assert_eq!(
+        state,
+        SubmarineState {
+            horizontal: 2,
+            lateral: -3,
+            depth: 16,
+            aim: 1,
+            heading: 180,
+            drift: (0.0, 0.0),
+        }
+    );
+    assert_eq!(DirectModel.navigate(&values).horizontal, 11);
+    let script = Script::parse("turn left 90\nturn right 180").unwrap();
+    assert_eq!(script.expand(), vec![Move::Turn(-90), Move::Turn(180)]);
+}
+
+#[test]
+fn heading_model_angles() {
+    let script = Script::parse("down 1\nturn right 45\nforward 10\nforward 10").unwrap();
+    let trajectory = HeadingModel.trajectory(&script.expand());
+    let position = |i: usize| {
+        let s = trajectory.states[i];
+        (s.horizontal, s.lateral, s.depth)
+    };
+    assert_eq!(position(3), (7, 7, 10));
+    // 14.14 after the second move, not twice the rounded first one
+    assert_eq!(position(4), (14, 14, 20));
+    assert_eq!(trajectory.states[4].heading, 45);
+    // an equilateral triangle returns to the start without drifting away
+    let script = Script::parse("const T = 120\nrepeat 300 {\nforward 7\nturn left T\n}").unwrap();
+    let state = HeadingModel.navigate(&script.expand());
+    assert_eq!((state.horizontal, state.lateral, state.heading), (0, 0, 0));
+    assert!(state.drift.0.abs() < 1e-6 && state.drift.1.abs() < 1e-6);
+    let state = HeadingModel.navigate(&[Move::Turn(-30), Move::Forward(2)]);
+    assert_eq!(
+        (state.horizontal, state.lateral, state.heading),
+        (2, -1, 330)
+    );
+}
+
+#[test]
+fn trajectory_example() {
+    let values = read_file_into_vector("src/day2/example.txt");
+    let trajectory = AimModel.trajectory(&values);
+    assert_eq!(trajectory.states.len(), values.len() + 1);
+    assert_eq!(
+        *trajectory.states.last().unwrap(),
+        AimModel.navigate(&values)
+    );
+    assert_eq!(trajectory.max_depth(), 60);
+    assert_eq!(trajectory.first_deeper_than(30), Some(2));
+    assert_eq!(trajectory.first_deeper_than(60), None);
+    let expected =
+        5.0 + (8.0_f64 * 8.0 + 40.0 * 40.0).sqrt() + (2.0_f64 * 2.0 + 20.0 * 20.0).sqrt();
+    assert!((trajectory.distance() - expected).abs() < 1e-9);
+    let csv = trajectory.to_csv();
+    assert_eq!(csv.lines().count(), values.len() + 2);
+    assert_eq!(csv.lines().nth(4), Some("3,13,0,40,5,0"));
+    let svg = trajectory.to_svg();
+    assert!(svg.contains("viewBox=\"0 0 15 60\""));
+    assert!(svg.contains("points=\"0,0 5,0 5,0 13,40 13,40 13,40 15,60\""));
+}
+
+fn assert_round_trip(horizontal: isize, depth: isize, limits: Limits) -> usize {
+    let moves = plan(horizontal, depth, limits).unwrap();
+    let state = move_sum2(&moves);
+    assert_eq!((state.horizontal, state.depth), (horizontal, depth));
+    moves.iter().for_each(|m| match *m {
+        Move::Forward(x) => assert!(x > 0 && x <= limits.forward.unwrap_or(x)),
+        Move::Up(x) | Move::Down(x) => assert!(x > 0 && x <= limits.aim.unwrap_or(x)),
+        Move::Turn(_) => panic!("unexpected turn"),
+    });
+    moves.len()
+}
+
+#[test]
+fn plan_unlimited() {
+    let limits = Limits::default();
+    assert_eq!(assert_round_trip(0, 0, limits), 0);
+    assert_eq!(assert_round_trip(15, 0, limits), 1);
+    assert_eq!(assert_round_trip(15, 60, limits), 2);
+    assert_eq!(assert_round_trip(15, 61, limits), 3);
+    assert_eq!(assert_round_trip(15, -61, limits), 3);
+    let values = read_file_into_vector("src/day2/input.txt");
+    let target = move_sum2(&values);
+    assert!(assert_round_trip(target.horizontal, target.depth, limits) <= 3);
+    assert_eq!(assert_round_trip(1 << 50, 1 << 20, limits), 3);
+    assert_eq!(assert_round_trip(1 << 30, 3 << 30, limits), 2);
+    assert_eq!(assert_round_trip(1 << 50, 1_000_003, limits), 3);
+    assert_eq!(assert_round_trip(7, 36, limits), 3);
+    assert_eq!(
+        divisors(36).sorted().collect_vec(),
+        [1, 2, 3, 4, 6, 9, 12, 18, 36]
+    );
+}
+
+#[test]
+fn plan_limited() {
+    let limits = |forward, aim| Limits {
+        forward: Some(forward),
+        aim: Some(aim),
+        commands: None,
+    };
+    assert_eq!(assert_round_trip(15, 60, limits(5, 2)), 5);
+    assert_eq!(assert_round_trip(100, 5000, limits(7, 3)), 32);
+    assert_eq!(assert_round_trip(300, 7919, limits(4, 2)), 90);
+    let forward_only = Limits {
+        forward: Some(4),
+        aim: None,
+        commands: None,
+    };
+    assert_eq!(assert_round_trip(10, 7, forward_only), 4);
+}
+
+#[test]
+fn plan_unreachable() {
+    assert_eq!(plan(-1, 0, Limits::default()), Err(Unreachable));
+    assert_eq!(plan(0, 5, Limits::default()), Err(Unreachable));
+    let no_forward = Limits {
+        forward: Some(0),
+        aim: None,
+        commands: None,
+    };
+    assert_eq!(plan(3, 0, no_forward), Err(Unreachable));
+    let no_aim = Limits {
+        forward: None,
+        aim: Some(0),
+        commands: None,
+    };
+    assert_eq!(plan(3, 1, no_aim), Err(Unreachable));
+    assert_eq!(plan(3, 0, no_aim), Ok(vec![Move::Forward(3)]));
+    let negative_aim = Limits {
+        forward: None,
+        aim: Some(-1),
+        commands: None,
+    };
+    assert_eq!(plan(3, 1, negative_aim), Err(Unreachable));
+    assert_eq!(plan(3, -1, negative_aim), Err(Unreachable));
+    let short = |commands| Limits {
+        forward: Some(10),
+        aim: Some(3),
+        commands: Some(commands),
+    };
+    // at least 200 forward and 167 aim commands, so the search stops early
+    assert_eq!(plan(2000, 1_000_003, short(300)), Err(Unreachable));
+    assert_eq!(plan(5, 6, short(2)), Err(Unreachable));
+    assert_eq!(plan(5, 6, short(3)).map(|m| m.len()), Ok(3));
+    let unlimited = Limits {
+        commands: Some(2),
+        ..Limits::default()
+    };
+    assert_eq!(plan(5, 7, unlimited), Err(Unreachable));
+}
+
+#[test]
+fn plan_huge_aim() {
+    let limits = |forward, aim| Limits {
+        forward: Some(forward),
+        aim: Some(aim),
+        commands: None,
+    };
+    assert_eq!(assert_round_trip(10, 5, limits(10, 1 << 62)), 3);
+    assert_eq!(assert_round_trip(10, -5, limits(4, isize::MAX)), 5);
+    assert_eq!(
+        assert_round_trip(1 << 40, 1 << 40, limits(1 << 40, 1 << 62)),
+        2
+    );
+    assert_eq!(
+        assert_round_trip(1 << 40, -(1 << 41), limits(1 << 40, 1 << 62)),
+        2
+    );
+    assert_eq!(
+        plan(10, 5, limits(10, 1 << 62)).map(|m| m[1]),
+        Ok(Move::Down(1))
+    );
+}
+
+// breadth first search over every program, for small targets only
+fn shortest_brute_force(horizontal: isize, depth: isize, limits: (isize, isize)) -> usize {
+    let mut frontier = vec![(0, 0, 0)];
+    let mut seen: HashSet<(isize, isize, isize)> = frontier.iter().copied().collect();
+    for length in 0.. {
+        if frontier
+            .iter()
+            .any(|&(h, _, d)| h == horizontal && d == depth)
+        {
+            return length;
+        }
+        let mut next = vec![];
+        frontier.iter().for_each(|&(h, a, d)| {
+            let forward = (1..=limits.0.min(horizontal - h)).map(|x| (h + x, a, d + x * a));
+            let aim = (1..=limits.1).flat_map(|x| [(h, a + x, d), (h, a - x, d)]);
+            forward.chain(aim).for_each(|s| {
+                if s.1.abs() <= depth.abs() + limits.1 && seen.insert(s) {
+                    next.push(s);
+                }
+            });
+        });
+        frontier = next;
+    }
+    unreachable!()
+}
+
+#[test]
+fn plan_is_shortest() {
+    for limits in [(2, 3), (3, 2), (4, 1)] {
+        for horizontal in 1..6 {
+            for depth in -8..16 {
+                let planned = assert_round_trip(
+                    horizontal,
+                    depth,
+                    Limits {
+                        forward: Some(limits.0),
+                        aim: Some(limits.1),
+                        commands: None,
+                    },
+                );
+                assert_eq!(planned, shortest_brute_force(horizontal, depth, limits));
+            }
+        }
+    }
+}
+
+#[test]
+fn replay_seabed() {
+    let seabed = read_seabed("src/day2/seabed.txt", 1, 10);
+    let values = read_file_into_vector("src/day2/example.txt");
+    let report = replay(&AimModel, &values, &seabed, 10);
+    let contact = |command, horizontal, depth, clearance| Contact {
+        command,
+        horizontal,
+        depth,
+        clearance,
+    };
+    assert_eq!(report.collision, Some(contact(5, 14, 50, 0)));
+    assert_eq!(report.min_clearance, report.collision);
+    assert_eq!(
+        report.near_misses,
+        vec![contact(2, 12, 35, 5), contact(2, 13, 40, 10)]
+    );
+    let report = replay(&DirectModel, &values, &seabed, 10);
+    assert_eq!(report.collision, None);
+    assert_eq!(report.min_clearance, Some(contact(2, 12, 5, 35)));
+    assert!(report.near_misses.is_empty());
+}
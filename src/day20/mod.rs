@@ -0,0 +1,129 @@
+use crate::utils;
+use itertools::Itertools;
+
+type Algorithm = Vec<bool>;
+
+struct Image {
+    points: Vec<Vec<bool>>,
+    width: isize,
+    height: isize,
+    // value of every pixel outside of `points`
+    void: bool,
+}
+
+impl Image {
+    fn point(&self, x: isize, y: isize) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            self.void
+        } else {
+            self.points[y as usize][x as usize]
+        }
+    }
+
+    fn index(&self, x: isize, y: isize) -> usize {
+        (y - 1..=y + 1)
+            .cartesian_product(x - 1..=x + 1)
+            .fold(0, |acc, (y, x)| (acc << 1) | self.point(x, y) as usize)
+    }
+
+    fn lit(&self) -> usize {
+        assert!(!self.void, "Infinite number of lit pixels");
+        self.points.iter().flatten().filter(|&&p| p).count()
+    }
+}
+
+fn parse_pixel(c: char) -> bool {
+    match c {
+        '#' => true,
+        '.' => false,
+        _ => panic!("Unexpected pixel"),
+    }
+}
+
+// the first row is the algorithm, the image follows after an empty row
+fn read_file(path: &str) -> (Algorithm, Image) {
+    let mut rows = utils::read_file_into_grid(path, parse_pixel);
+    assert!(rows.len() > 2 && rows[1].is_empty(), "Malformed input");
+    let points = rows.split_off(2);
+    let algorithm = rows.swap_remove(0);
+    assert_eq!(algorithm.len(), 512);
+    let width = points[0].len() as isize;
+    let height = points.len() as isize;
+    (
+        algorithm,
+        Image {
+            points,
+            width,
+            height,
+            void: false,
+        },
+    )
+}
+
+fn enhance(algorithm: &Algorithm, image: Image) -> Image {
+    let points = (-1..image.height + 1)
+        .map(|y| {
+            (-1..image.width + 1)
+                .map(|x| algorithm[image.index(x, y)])
+                .collect_vec()
+        })
+        .collect_vec();
+    Image {
+        points,
+        width: image.width + 2,
+        height: image.height + 2,
+        void: algorithm[if image.void { 511 } else { 0 }],
+    }
+}
+
+fn lit_after(algorithm: &Algorithm, image: Image, steps: usize) -> usize {
+    (0..steps)
+        .fold(image, |image, _| enhance(algorithm, image))
+        .lit()
+}
+
+#[test]
+fn task1_example() {
+    let (algorithm, image) = read_file("src/day20/example.txt");
+    let result = lit_after(&algorithm, image, 2);
+    println!("D20T1E {}", result);
+    assert_eq!(result, 35);
+}
+
+#[test]
+fn task1_puzzle() {
+    let (algorithm, image) = read_file("src/day20/input.txt");
+    let result = lit_after(&algorithm, image, 2);
+    println!("D20T1P {}", result);
+    assert_eq!(result, 5723);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_example() {
+    let (algorithm, image) = read_file("src/day20/example.txt");
+    let result = lit_after(&algorithm, image, 50);
+    println!("D20T2E {}", result);
+    assert_eq!(result, 3351);
+}
+
+#[test]
+fn task2_puzzle() {
+    let (algorithm, image) = read_file("src/day20/input.txt");
+    let result = lit_after(&algorithm, image, 50);
+    println!("D20T2P {}", result);
+    assert_eq!(result, 19996);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
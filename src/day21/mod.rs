@@ -0,0 +1,135 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+
+const TRACK_SIZE: usize = 10;
+const PRACTICE_SCORE: usize = 1000;
+const QUANTUM_SCORE: u8 = 21;
+
+// sums of three 3-sided dice rolls and the number of universes producing them
+static ROLLS: [(u8, usize); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+type Positions = [u8; 2];
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+struct State {
+    positions: Positions,
+    scores: [u8; 2],
+    turn: usize,
+}
+
+type Cache = HashMap<State, [usize; 2]>;
+
+fn read_file(path: &str) -> Positions {
+    std::fs::read_to_string(path)
+        .expect("Missing input data")
+        .lines()
+        .map(|l| {
+            let (_, position) = l.split_once(": ").expect("Malformed line");
+            let position = position.parse::<u8>().expect("Position not a number");
+            assert!(position >= 1 && position as usize <= TRACK_SIZE);
+            position
+        })
+        .collect_vec()
+        .try_into()
+        .expect("Expected two players")
+}
+
+fn advance(position: u8, steps: usize) -> u8 {
+    ((position as usize - 1 + steps) % TRACK_SIZE + 1) as u8
+}
+
+fn play_practice(positions: Positions) -> usize {
+    let mut positions = positions;
+    let mut scores = [0_usize; 2];
+    let mut die = (1..=100).cycle();
+    let mut rolls = 0;
+    for turn in (0..2).cycle() {
+        let steps: usize = die.by_ref().take(3).sum();
+        rolls += 3;
+        positions[turn] = advance(positions[turn], steps);
+        scores[turn] += positions[turn] as usize;
+        if scores[turn] >= PRACTICE_SCORE {
+            return scores[1 - turn] * rolls;
+        }
+    }
+    unreachable!()
+}
+
+fn count_wins(state: State, cache: &mut Cache) -> [usize; 2] {
+    if let Some(&wins) = cache.get(&state) {
+        return wins;
+    }
+    let turn = state.turn;
+    let mut wins = [0; 2];
+    ROLLS.iter().for_each(|&(steps, universes)| {
+        let mut next = state;
+        next.positions[turn] = advance(state.positions[turn], steps as usize);
+        next.scores[turn] += next.positions[turn];
+        if next.scores[turn] >= QUANTUM_SCORE {
+            wins[turn] += universes;
+        } else {
+            next.turn = 1 - turn;
+            let sub = count_wins(next, cache);
+            wins[0] += sub[0] * universes;
+            wins[1] += sub[1] * universes;
+        }
+    });
+    cache.insert(state, wins);
+    wins
+}
+
+fn play_quantum(positions: Positions) -> usize {
+    let state = State {
+        positions,
+        scores: [0; 2],
+        turn: 0,
+    };
+    let wins = count_wins(state, &mut Cache::new());
+    wins[0].max(wins[1])
+}
+
+#[test]
+fn task1_example() {
+    let positions = read_file("src/day21/example.txt");
+    let result = play_practice(positions);
+    println!("D21T1E {}", result);
+    assert_eq!(result, 739785);
+}
+
+#[test]
+fn task1_puzzle() {
+    let positions = read_file("src/day21/input.txt");
+    let result = play_practice(positions);
+    println!("D21T1P {}", result);
+    assert_eq!(result, 551901);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_example() {
+    let positions = read_file("src/day21/example.txt");
+    let result = play_quantum(positions);
+    println!("D21T2E {}", result);
+    assert_eq!(result, 444356092776315);
+}
+
+#[test]
+fn task2_puzzle() {
+    let positions = read_file("src/day21/input.txt");
+    let result = play_quantum(positions);
+    println!("D21T2P {}", result);
+    assert_eq!(result, 272847859601291);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
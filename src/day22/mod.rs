@@ -0,0 +1,228 @@
+use crate::utils;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Cuboid {
+    x: (isize, isize),
+    y: (isize, isize),
+    z: (isize, isize),
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Step {
+    on: bool,
+    cuboid: Cuboid,
+}
+
+const INITIALIZATION_REGION: Cuboid = Cuboid {
+    x: (-50, 50),
+    y: (-50, 50),
+    z: (-50, 50),
+};
+
+fn intersect_range(a: (isize, isize), b: (isize, isize)) -> Option<(isize, isize)> {
+    let r = (a.0.max(b.0), a.1.min(b.1));
+    if r.0 <= r.1 {
+        Some(r)
+    } else {
+        None
+    }
+}
+
+impl Cuboid {
+    fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        Some(Cuboid {
+            x: intersect_range(self.x, other.x)?,
+            y: intersect_range(self.y, other.y)?,
+            z: intersect_range(self.z, other.z)?,
+        })
+    }
+
+    fn volume(&self) -> isize {
+        (self.x.1 - self.x.0 + 1) * (self.y.1 - self.y.0 + 1) * (self.z.1 - self.z.0 + 1)
+    }
+}
+
+fn parse_range(s: &str) -> (isize, isize) {
+    let (_, range) = s.split_once('=').expect("Malformed range");
+    let (from, to) = range.split_once("..").expect("Malformed range");
+    (from.parse().unwrap(), to.parse().unwrap())
+}
+
+fn read_file(path: &str) -> Vec<Step> {
+    utils::read_file_into_vector(path, |l| {
+        let (state, ranges) = l.split_once(' ').expect("Malformed line");
+        let on = match state {
+            "on" => true,
+            "off" => false,
+            _ => panic!("Unexpected state"),
+        };
+        let mut ranges = ranges.split(',').map(parse_range);
+        Step {
+            on,
+            cuboid: Cuboid {
+                x: ranges.next().unwrap(),
+                y: ranges.next().unwrap(),
+                z: ranges.next().unwrap(),
+            },
+        }
+    })
+}
+
+// every cuboid intersecting an already counted one adds that intersection
+// with the opposite sign, so overlapping volume is counted exactly once
+fn count_lit<'a, I>(steps: I) -> usize
+where
+    I: Iterator<Item = &'a Step>,
+{
+    let mut signed: Vec<(Cuboid, isize)> = vec![];
+    steps.for_each(|step| {
+        let overlaps = signed
+            .iter()
+            .filter_map(|(c, sign)| {
+                step.cuboid
+                    .intersection(c)
+                    .map(|intersection| (intersection, -sign))
+            })
+            .collect::<Vec<_>>();
+        signed.extend(overlaps);
+        if step.on {
+            signed.push((step.cuboid, 1));
+        }
+    });
+    signed
+        .iter()
+        .map(|(c, sign)| c.volume() * sign)
+        .sum::<isize>() as usize
+}
+
+fn count_lit_initialization(steps: &[Step]) -> usize {
+    let clipped = steps
+        .iter()
+        .filter_map(|s| {
+            s.cuboid
+                .intersection(&INITIALIZATION_REGION)
+                .map(|cuboid| Step { on: s.on, cuboid })
+        })
+        .collect::<Vec<_>>();
+    count_lit(clipped.iter())
+}
+
+fn count_lit_all(steps: &[Step]) -> usize {
+    count_lit(steps.iter())
+}
+
+#[test]
+fn task1_example() {
+    let steps = read_file("src/day22/example.txt");
+    let result = count_lit_initialization(&steps);
+    println!("D22T1E {}", result);
+    assert_eq!(result, 39);
+}
+
+#[test]
+fn task1_puzzle() {
+    let steps = read_file("src/day22/input.txt");
+    let result = count_lit_initialization(&steps);
+    println!("D22T1P {}", result);
+    assert_eq!(result, 136675);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_example() {
+    let steps = read_file("src/day22/example2.txt");
+    let result = count_lit_all(&steps);
+    println!("D22T2E {}", result);
+    assert_eq!(result, 1056);
+    assert_eq!(count_lit_initialization(&steps), 24);
+}
+
+#[test]
+fn task2_puzzle() {
+    let steps = read_file("src/day22/input.txt");
+    let result = count_lit_all(&steps);
+    println!("D22T2P {}", result);
+    assert_eq!(result, 324518020763953);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
+
+// lights every cube one by one, for small cuboids only
+fn count_lit_brute_force(steps: &[Step], region: Option<Cuboid>) -> usize {
+    let mut lit = std::collections::HashSet::new();
+    steps.iter().for_each(|s| {
+        let c = s.cuboid;
+        for x in c.x.0..=c.x.1 {
+            for y in c.y.0..=c.y.1 {
+                for z in c.z.0..=c.z.1 {
+                    if s.on {
+                        lit.insert((x, y, z));
+                    } else {
+                        lit.remove(&(x, y, z));
+                    }
+                }
+            }
+        }
+    });
+    let inside = |&&(x, y, z): &&(isize, isize, isize)| {
+        region
+            .map(|r| {
+                r.intersection(&Cuboid {
+                    x: (x, x),
+                    y: (y, y),
+                    z: (z, z),
+                })
+                .is_some()
+            })
+            .unwrap_or(true)
+    };
+    lit.iter().filter(inside).count()
+}
+
+#[test]
+fn brute_force_past_initialization() {
+    let mut seed = 1_u64;
+    let mut next = |n: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % n) as isize
+    };
+    for _ in 0..20 {
+        // cuboids straddle the edge of the initialization region
+        let steps = (0..20)
+            .map(|_| {
+                let on = next(3) > 0;
+                let mut range = || {
+                    let from = next(30) + 35;
+                    let from = if next(2) == 0 { from } else { -from - 8 };
+                    (from, from + next(8))
+                };
+                Step {
+                    on,
+                    cuboid: Cuboid {
+                        x: range(),
+                        y: range(),
+                        z: range(),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(count_lit_all(&steps), count_lit_brute_force(&steps, None));
+        assert_eq!(
+            count_lit_initialization(&steps),
+            count_lit_brute_force(&steps, Some(INITIALIZATION_REGION))
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use itertools::Itertools;
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+const HALLWAY_SIZE: usize = 11;
+const ROOMS: usize = 4;
+const MAX_DEPTH: usize = 4;
+const EMPTY: u8 = 0;
+
+// hallway cells right outside of every room, amphipods never stop there
+static DOORS: [usize; ROOMS] = [2, 4, 6, 8];
+static STOPS: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
+static ENERGY: [usize; ROOMS] = [1, 10, 100, 1000];
+
+// rows inserted between the two folded rows, top to bottom
+static UNFOLDED: [[u8; ROOMS]; 2] = [[4, 3, 2, 1], [4, 2, 1, 3]];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Cell {
+    Hallway(usize),
+    Room(usize, usize),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Move {
+    amphipod: u8,
+    from: Cell,
+    to: Cell,
+    energy: usize,
+}
+
+// amphipods are 1..=4 for A..D, rooms are listed top to bottom
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Burrow {
+    hallway: [u8; HALLWAY_SIZE],
+    rooms: [[u8; MAX_DEPTH]; ROOMS],
+    depth: usize,
+}
+
+// 3 bits per cell, 27 cells and the depth fit into u128
+type State = u128;
+
+type UnvisitedQueue = PriorityQueue<State, Reverse<usize>>;
+
+impl Burrow {
+    fn encode(&self) -> State {
+        self.hallway
+            .iter()
+            .chain(self.rooms.iter().flatten())
+            .fold(self.depth as State, |acc, &a| (acc << 3) | a as State)
+    }
+
+    fn decode(state: State) -> Burrow {
+        let mut state = state;
+        let mut burrow = Burrow {
+            hallway: [EMPTY; HALLWAY_SIZE],
+            rooms: [[EMPTY; MAX_DEPTH]; ROOMS],
+            depth: 0,
+        };
+        burrow
+            .rooms
+            .iter_mut()
+            .rev()
+            .flat_map(|r| r.iter_mut().rev())
+            .chain(burrow.hallway.iter_mut().rev())
+            .for_each(|a| {
+                *a = (state & 0x7) as u8;
+                state >>= 3;
+            });
+        burrow.depth = state as usize;
+        burrow
+    }
+
+    fn is_organized(&self) -> bool {
+        (0..ROOMS).all(|r| {
+            self.rooms[r][..self.depth]
+                .iter()
+                .all(|&a| a == r as u8 + 1)
+        })
+    }
+
+    // room contains only amphipods that belong there
+    fn is_settled(&self, room: usize) -> bool {
+        self.rooms[room][..self.depth]
+            .iter()
+            .all(|&a| a == EMPTY || a == room as u8 + 1)
+    }
+
+    fn is_hallway_clear(&self, from: usize, to: usize) -> bool {
+        let (lo, hi) = if from < to { (from, to) } else { (to, from) };
+        (lo..=hi).all(|x| x == from || self.hallway[x] == EMPTY)
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        (0..HALLWAY_SIZE)
+            .filter(|&x| self.hallway[x] != EMPTY)
+            .for_each(|x| {
+                let amphipod = self.hallway[x];
+                let room = amphipod as usize - 1;
+                if self.is_settled(room) && self.is_hallway_clear(x, DOORS[room]) {
+                    let y = (0..self.depth)
+                        .rev()
+                        .find(|&y| self.rooms[room][y] == EMPTY)
+                        .unwrap();
+                    moves.push(Move {
+                        amphipod,
+                        from: Cell::Hallway(x),
+                        to: Cell::Room(room, y),
+                        energy: (x.abs_diff(DOORS[room]) + y + 1) * ENERGY[room],
+                    });
+                }
+            });
+        (0..ROOMS)
+            .filter(|&room| !self.is_settled(room))
+            .for_each(|room| {
+                let y = (0..self.depth)
+                    .find(|&y| self.rooms[room][y] != EMPTY)
+                    .unwrap();
+                let amphipod = self.rooms[room][y];
+                STOPS
+                    .iter()
+                    .filter(|&&x| self.hallway[x] == EMPTY)
+                    .filter(|&&x| self.is_hallway_clear(DOORS[room], x))
+                    .for_each(|&x| {
+                        moves.push(Move {
+                            amphipod,
+                            from: Cell::Room(room, y),
+                            to: Cell::Hallway(x),
+                            energy: (x.abs_diff(DOORS[room]) + y + 1)
+                                * ENERGY[amphipod as usize - 1],
+                        })
+                    });
+            });
+        moves
+    }
+
+    fn cell_mut(&mut self, cell: Cell) -> &mut u8 {
+        match cell {
+            Cell::Hallway(x) => &mut self.hallway[x],
+            Cell::Room(room, y) => &mut self.rooms[room][y],
+        }
+    }
+
+    fn apply(&self, m: &Move) -> Burrow {
+        let mut next = *self;
+        *next.cell_mut(m.from) = EMPTY;
+        *next.cell_mut(m.to) = m.amphipod;
+        next
+    }
+}
+
+fn parse_amphipod(c: char) -> u8 {
+    match c {
+        'A' => 1,
+        'B' => 2,
+        'C' => 3,
+        'D' => 4,
+        '.' => EMPTY,
+        _ => panic!("Unexpected amphipod"),
+    }
+}
+
+fn read_file(path: &str) -> Burrow {
+    let input = std::fs::read_to_string(path).expect("Missing input data");
+    let lines = input.lines().collect_vec();
+    let hallway: [u8; HALLWAY_SIZE] = lines[1][1..=HALLWAY_SIZE]
+        .chars()
+        .map(parse_amphipod)
+        .collect_vec()
+        .try_into()
+        .expect("Malformed hallway");
+    let rows = lines[2..lines.len() - 1]
+        .iter()
+        .map(|l| {
+            let chars = l.chars().collect_vec();
+            DOORS.map(|x| parse_amphipod(chars[x + 1]))
+        })
+        .collect_vec();
+    assert!(rows.len() <= MAX_DEPTH, "Rooms are too deep");
+    let mut rooms = [[EMPTY; MAX_DEPTH]; ROOMS];
+    rows.iter().enumerate().for_each(|(y, row)| {
+        (0..ROOMS).for_each(|room| rooms[room][y] = row[room]);
+    });
+    Burrow {
+        hallway,
+        rooms,
+        depth: rows.len(),
+    }
+}
+
+fn unfold(burrow: Burrow) -> Burrow {
+    assert_eq!(burrow.depth, 2, "Burrow is already unfolded");
+    let mut unfolded = burrow;
+    (0..ROOMS).for_each(|room| {
+        unfolded.rooms[room] = [
+            burrow.rooms[room][0],
+            UNFOLDED[0][room],
+            UNFOLDED[1][room],
+            burrow.rooms[room][1],
+        ];
+    });
+    unfolded.depth = MAX_DEPTH;
+    unfolded
+}
+
+fn find_path(burrow: Burrow) -> (usize, Vec<Move>) {
+    let mut frontier = UnvisitedQueue::new();
+    let mut visited = HashSet::new();
+    let mut best = HashMap::new();
+    let mut previous: HashMap<State, (State, Move)> = HashMap::new();
+    let start = burrow.encode();
+    frontier.push(start, Reverse(0));
+    while let Some((state, energy)) = frontier.pop() {
+        let current = Burrow::decode(state);
+        if current.is_organized() {
+            let mut moves = vec![];
+            let mut state = state;
+            while let Some(&(prev, m)) = previous.get(&state) {
+                moves.push(m);
+                state = prev;
+            }
+            moves.reverse();
+            return (energy.0, moves);
+        }
+        visited.insert(state);
+        current.moves().into_iter().for_each(|m| {
+            let next = current.apply(&m).encode();
+            let alt = energy.0 + m.energy;
+            if !visited.contains(&next) && alt < *best.get(&next).unwrap_or(&usize::MAX) {
+                best.insert(next, alt);
+                previous.insert(next, (state, m));
+                frontier.push_increase(next, Reverse(alt));
+            }
+        });
+    }
+    panic!("Burrow cannot be organized");
+}
+
+fn least_energy(burrow: Burrow) -> usize {
+    find_path(burrow).0
+}
+
+#[test]
+fn encoding_test() {
+    let burrow = unfold(read_file("src/day23/example.txt"));
+    assert_eq!(Burrow::decode(burrow.encode()), burrow);
+}
+
+#[test]
+fn moves_test() {
+    let burrow = read_file("src/day23/example.txt");
+    let (energy, moves) = find_path(burrow);
+    let organized = moves.iter().fold(burrow, |b, m| b.apply(m));
+    assert!(organized.is_organized());
+    assert_eq!(moves.iter().map(|m| m.energy).sum::<usize>(), energy);
+}
+
+#[test]
+fn task1_example() {
+    let burrow = read_file("src/day23/example.txt");
+    let result = least_energy(burrow);
+    println!("D23T1E {}", result);
+    assert_eq!(result, 12521);
+}
+
+#[test]
+fn task1_puzzle() {
+    let burrow = read_file("src/day23/input.txt");
+    let result = least_energy(burrow);
+    println!("D23T1P {}", result);
+    assert_eq!(result, 17050);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_example() {
+    let burrow = unfold(read_file("src/day23/example.txt"));
+    let result = least_energy(burrow);
+    println!("D23T2E {}", result);
+    assert_eq!(result, 44169);
+}
+
+#[test]
+fn task2_puzzle() {
+    let burrow = unfold(read_file("src/day23/input.txt"));
+    let result = least_energy(burrow);
+    println!("D23T2P {}", result);
+    assert_eq!(result, 46942);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
@@ -0,0 +1,243 @@
+use crate::utils;
+use itertools::Itertools;
+
+const DIGITS: usize = 14;
+const BLOCK_SIZE: usize = 18;
+const Z: usize = 3;
+
+type Registers = [isize; 4];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Operand {
+    Register(usize),
+    Literal(isize),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+// the part of a digit block that differs between blocks
+#[derive(Debug, Copy, Clone)]
+struct Block {
+    divisor: isize,
+    check: isize,
+    offset: isize,
+}
+
+// digits[pop] == digits[push] + diff
+#[derive(Debug, Copy, Clone)]
+struct Constraint {
+    push: usize,
+    pop: usize,
+    diff: isize,
+}
+
+fn parse_register(s: &str) -> usize {
+    match s {
+        "w" => 0,
+        "x" => 1,
+        "y" => 2,
+        "z" => 3,
+        _ => panic!("Unexpected register"),
+    }
+}
+
+fn parse_operand(s: &str) -> Operand {
+    match s.parse::<isize>() {
+        Ok(v) => Operand::Literal(v),
+        Err(_) => Operand::Register(parse_register(s)),
+    }
+}
+
+fn read_file(path: &str) -> Vec<Instruction> {
+    utils::read_file_into_vector(path, |l| {
+        let mut parts = l.split(' ');
+        let op = parts.next().expect("Missing operation");
+        let a = parse_register(parts.next().expect("Missing register"));
+        if op == "inp" {
+            return Instruction::Inp(a);
+        }
+        let b = parse_operand(parts.next().expect("Missing operand"));
+        match op {
+            "add" => Instruction::Add(a, b),
+            "mul" => Instruction::Mul(a, b),
+            "div" => Instruction::Div(a, b),
+            "mod" => Instruction::Mod(a, b),
+            "eql" => Instruction::Eql(a, b),
+            _ => panic!("Unexpected operation"),
+        }
+    })
+}
+
+fn value(registers: &Registers, operand: Operand) -> isize {
+    match operand {
+        Operand::Register(r) => registers[r],
+        Operand::Literal(v) => v,
+    }
+}
+
+fn calculate<I>(program: &[Instruction], input: I) -> Registers
+where
+    I: IntoIterator<Item = isize>,
+{
+    let mut input = input.into_iter();
+    let mut registers = [0; 4];
+    program.iter().for_each(|&instruction| match instruction {
+        Instruction::Inp(a) => registers[a] = input.next().expect("Missing input"),
+        Instruction::Add(a, b) => registers[a] += value(&registers, b),
+        Instruction::Mul(a, b) => registers[a] *= value(&registers, b),
+        Instruction::Div(a, b) => {
+            let b = value(&registers, b);
+            assert_ne!(b, 0);
+            registers[a] /= b;
+        }
+        Instruction::Mod(a, b) => {
+            let b = value(&registers, b);
+            assert!(registers[a] >= 0 && b > 0);
+            registers[a] %= b;
+        }
+        Instruction::Eql(a, b) => {
+            registers[a] = if registers[a] == value(&registers, b) {
+                1
+            } else {
+                0
+            }
+        }
+    });
+    registers
+}
+
+fn is_valid(program: &[Instruction], digits: &[isize]) -> bool {
+    calculate(program, digits.iter().copied())[Z] == 0
+}
+
+fn literal(instruction: Instruction) -> isize {
+    match instruction {
+        Instruction::Div(_, Operand::Literal(v)) | Instruction::Add(_, Operand::Literal(v)) => v,
+        _ => panic!("Unexpected block structure"),
+    }
+}
+
+// every digit block either pushes `digit + offset` onto the base 26 stack in z
+// or pops the top and compares it, after adding `check`, with the digit
+fn split_blocks(program: &[Instruction]) -> Vec<Block> {
+    assert_eq!(
+        program.len(),
+        DIGITS * BLOCK_SIZE,
+        "Unexpected program size"
+    );
+    let blocks = program.chunks(BLOCK_SIZE).collect_vec();
+    blocks
+        .iter()
+        .map(|block| {
+            block
+                .iter()
+                .zip(blocks[0].iter())
+                .enumerate()
+                .for_each(|(i, (a, b))| {
+                    assert!(
+                        i == 4 || i == 5 || i == 15 || a == b,
+                        "Unexpected block structure"
+                    )
+                });
+            Block {
+                divisor: literal(block[4]),
+                check: literal(block[5]),
+                offset: literal(block[15]),
+            }
+        })
+        .collect_vec()
+}
+
+fn derive_constraints(blocks: &[Block]) -> Vec<Constraint> {
+    let mut stack = vec![];
+    let mut constraints = vec![];
+    blocks
+        .iter()
+        .enumerate()
+        .for_each(|(i, b)| match b.divisor {
+            1 => stack.push(i),
+            26 => {
+                let push = stack.pop().expect("Unbalanced program");
+                constraints.push(Constraint {
+                    push,
+                    pop: i,
+                    diff: blocks[push].offset + b.check,
+                });
+            }
+            _ => panic!("Unexpected divisor"),
+        });
+    assert!(stack.is_empty(), "Unbalanced program");
+    constraints
+}
+
+// picks the push digit preferred by `choose` that keeps both digits in 1..=9
+fn model_number<F>(program: &[Instruction], choose: F) -> usize
+where
+    F: Fn(isize, isize) -> isize,
+{
+    let mut digits = [0; DIGITS];
+    derive_constraints(&split_blocks(program))
+        .iter()
+        .for_each(|c| {
+            let lo = 1.max(1 - c.diff);
+            let hi = 9.min(9 - c.diff);
+            assert!(lo <= hi, "No valid model number");
+            digits[c.push] = choose(lo, hi);
+            digits[c.pop] = digits[c.push] + c.diff;
+        });
+    assert!(is_valid(program, &digits));
+    digits.iter().fold(0, |acc, &d| acc * 10 + d as usize)
+}
+
+fn largest_model_number(program: &[Instruction]) -> usize {
+    model_number(program, |_, hi| hi)
+}
+
+fn smallest_model_number(program: &[Instruction]) -> usize {
+    model_number(program, |lo, _| lo)
+}
+
+#[test]
+fn calculate_test() {
+    let program = read_file("src/day24/example.txt");
+    assert_eq!(calculate(&program, [13]), [1, 1, 0, 1]);
+    assert_eq!(calculate(&program, [6]), [0, 1, 1, 0]);
+}
+
+#[test]
+fn task1_puzzle() {
+    let program = read_file("src/day24/input.txt");
+    let result = largest_model_number(&program);
+    println!("D24T1P {}", result);
+    assert_eq!(result, 19397593989799);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
+
+#[test]
+fn task2_puzzle() {
+    let program = read_file("src/day24/input.txt");
+    let result = smallest_model_number(&program);
+    println!("D24T2P {}", result);
+    assert_eq!(result, 17191131217153);
+}
+
+#[bench]
+fn task2_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task2_puzzle();
+    });
+}
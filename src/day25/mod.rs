@@ -0,0 +1,80 @@
+use crate::utils;
+use itertools::Itertools;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Cell {
+    Empty,
+    East,
+    South,
+}
+
+struct Grid {
+    points: Vec<Vec<Cell>>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid {
+    // moves every cucumber of the herd at once, returns the number of moves
+    fn move_herd(&mut self, herd: Cell, dx: usize, dy: usize) -> usize {
+        let moves = (0..self.height)
+            .cartesian_product(0..self.width)
+            .filter(|&(y, x)| {
+                self.points[y][x] == herd
+                    && self.points[(y + dy) % self.height][(x + dx) % self.width] == Cell::Empty
+            })
+            .collect_vec();
+        moves.iter().for_each(|&(y, x)| {
+            self.points[y][x] = Cell::Empty;
+            self.points[(y + dy) % self.height][(x + dx) % self.width] = herd;
+        });
+        moves.len()
+    }
+
+    fn step(&mut self) -> usize {
+        self.move_herd(Cell::East, 1, 0) + self.move_herd(Cell::South, 0, 1)
+    }
+}
+
+fn read_file(path: &str) -> Grid {
+    let points = utils::read_file_into_grid(path, |c| match c {
+        '.' => Cell::Empty,
+        '>' => Cell::East,
+        'v' => Cell::South,
+        _ => panic!("Unexpected cell"),
+    });
+    let width = points[0].len();
+    let height = points.len();
+    Grid {
+        points,
+        width,
+        height,
+    }
+}
+
+fn steps_to_gridlock(mut grid: Grid) -> usize {
+    (1..).find(|_| grid.step() == 0).unwrap()
+}
+
+#[test]
+fn task1_example() {
+    let grid = read_file("src/day25/example.txt");
+    let result = steps_to_gridlock(grid);
+    println!("D25T1E {}", result);
+    assert_eq!(result, 58);
+}
+
+#[test]
+fn task1_puzzle() {
+    let grid = read_file("src/day25/input.txt");
+    let result = steps_to_gridlock(grid);
+    println!("D25T1P {}", result);
+    assert_eq!(result, 557);
+}
+
+#[bench]
+fn task1_puzzle_bench(b: &mut test::Bencher) {
+    b.iter(|| {
+        task1_puzzle();
+    });
+}
@@ -1,20 +1,142 @@
 use crate::utils;
+use itertools::Itertools;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
-#[derive(Debug, Copy, Clone)]
+const WORD_BITS: usize = 64;
+
+// unsigned integer of any size, 64 bit limbs with the least significant first
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u64>) -> BigUint {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    fn from_digits<I>(digits: I, radix: usize) -> BigUint
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut limbs: Vec<u64> = vec![];
+        digits.into_iter().for_each(|d| {
+            let mut carry = d as u128;
+            limbs.iter_mut().for_each(|l| {
+                let cur = *l as u128 * radix as u128 + carry;
+                *l = cur as u64;
+                carry = cur >> WORD_BITS;
+            });
+            if carry > 0 {
+                limbs.push(carry as u64);
+            }
+        });
+        BigUint::from_limbs(limbs)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0_u64; self.limbs.len() + other.limbs.len()];
+        self.limbs.iter().enumerate().for_each(|(i, &a)| {
+            let mut carry = 0_u128;
+            other.limbs.iter().enumerate().for_each(|(j, &b)| {
+                let cur = limbs[i + j] as u128 + a as u128 * b as u128 + carry;
+                limbs[i + j] = cur as u64;
+                carry = cur >> WORD_BITS;
+            });
+            limbs[i + other.limbs.len()] = carry as u64;
+        });
+        BigUint::from_limbs(limbs)
+    }
+
+    fn div_rem_small(&self, divisor: u64) -> (BigUint, u64) {
+        let mut rem = 0_u128;
+        let mut limbs = vec![0; self.limbs.len()];
+        (0..self.limbs.len()).rev().for_each(|i| {
+            let cur = (rem << WORD_BITS) | self.limbs[i] as u128;
+            limbs[i] = (cur / divisor as u128) as u64;
+            rem = cur % divisor as u128;
+        });
+        (BigUint::from_limbs(limbs), rem as u64)
+    }
+}
+
+impl From<usize> for BigUint {
+    fn from(v: usize) -> BigUint {
+        BigUint::from_limbs(vec![v as u64])
+    }
+}
+
+impl PartialEq<usize> for BigUint {
+    fn eq(&self, other: &usize) -> bool {
+        self.limbs.len() <= 1 && self.limbs.first().copied().unwrap_or(0) == *other as u64
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u64 = 1_000_000_000_000_000_000;
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, rem) = rest.div_rem_small(CHUNK);
+            chunks.push(rem);
+            rest = quotient;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        chunks.iter().rev().try_for_each(|c| write!(f, "{:018}", c))
+    }
+}
+
+// bit `pos` counts from the left, it is stored at index `len - 1 - pos` of
+// the little endian words so the words are also the value of the line
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Line {
-    bits: usize,
+    bits: Vec<u64>,
     len: usize,
 }
 
 impl Line {
     pub fn new(str: &str) -> Line {
-        Line {
-            bits: usize::from_str_radix(str, 2).expect("Malformed string"),
-            len: str.len(),
-        }
+        Line::from_bools(str.chars().map(|c| match c {
+            '0' => false,
+            '1' => true,
+            _ => panic!("Malformed string"),
+        }))
+    }
+
+    pub fn from_bools<I>(it: I) -> Line
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let bools = it.into_iter().collect_vec();
+        let len = bools.len();
+        let mut bits = vec![0; len.div_ceil(WORD_BITS)];
+        bools
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| b)
+            .for_each(|(pos, _)| {
+                let index = len - 1 - pos;
+                bits[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+            });
+        Line { bits, len }
     }
+
     pub fn bool_at_pos(&self, pos: usize) -> bool {
-        (self.bits & (1 << (self.len - 1 - pos))) != 0
+        let index = self.len - 1 - pos;
+        (self.bits[index / WORD_BITS] >> (index % WORD_BITS)) & 1 != 0
     }
 
     pub fn value_at_pos(&self, pos: usize) -> usize {
@@ -28,73 +150,503 @@ impl Line {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    pub fn complement(&self) -> Line {
+        Line::from_bools((0..self.len).map(|pos| !self.bool_at_pos(pos)))
+    }
+}
+
+// the report transposed into one bitset per column, bit `row` of a column
+// holds that bit of the row, so counting a column is a popcount per word
+#[derive(Debug, Clone)]
+struct Columns {
+    columns: Vec<Vec<u64>>,
+    rows: usize,
+}
+
+impl Columns {
+    fn new(width: usize) -> Columns {
+        Columns {
+            columns: vec![vec![]; width],
+            rows: 0,
+        }
+    }
+
+    fn from_lines<'a, I>(it: I) -> Columns
+    where
+        I: IntoIterator<Item = &'a Line>,
+    {
+        let mut it = it.into_iter().peekable();
+        let mut columns = Columns::new(it.peek().expect("No lines").len());
+        it.for_each(|l| columns.push(l));
+        columns
+    }
+
+    fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    fn push(&mut self, line: &Line) {
+        assert_eq!(line.len(), self.width(), "Unexpected line length");
+        let (word, bit) = (self.rows / WORD_BITS, self.rows % WORD_BITS);
+        if bit == 0 {
+            self.columns.iter_mut().for_each(|c| c.push(0));
+        }
+        line.bits.iter().enumerate().for_each(|(i, &w)| {
+            let mut w = w;
+            while w != 0 {
+                let index = i * WORD_BITS + w.trailing_zeros() as usize;
+                self.columns[line.len() - 1 - index][word] |= 1 << bit;
+                w &= w - 1;
+            }
+        });
+        self.rows += 1;
+    }
+
+    fn count_ones(&self, pos: usize) -> usize {
+        self.columns[pos]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    // rows with a one in both columns
+    fn count_both(&self, a: usize, b: usize) -> usize {
+        self.columns[a]
+            .iter()
+            .zip(self.columns[b].iter())
+            .map(|(x, y)| (x & y).count_ones() as usize)
+            .sum()
+    }
+
+    fn counts(&self) -> Vec<usize> {
+        (0..self.width())
+            .map(|pos| self.count_ones(pos))
+            .collect_vec()
+    }
+
+    fn gamma_epsilon(&self) -> (BigUint, BigUint) {
+        let gamma = Line::from_bools(self.counts().iter().map(|&ones| ones * 2 >= self.rows));
+        (
+            bits_to_decimal(&gamma),
+            bits_to_decimal(&gamma.complement()),
+        )
+    }
 }
 
 fn read_file_into_vector(path: &str) -> Vec<Line> {
     utils::read_file_into_vector(path, Line::new)
 }
 
-fn gamma_epsilon(v: &Vec<Line>) -> (usize, usize) {
-    let mut ret = 0;
-    let l = v[0].len();
-    for x in 0..l {
-        ret += (if most_common_bit(v, x) { 1 } else { 0 }) << l - x - 1;
-    }
-    (ret, ((1 << l) - 1) ^ ret)
+// streams the rows straight into the columns without keeping them around
+fn read_file_into_columns(path: &str) -> Columns {
+    let mut lines = BufReader::new(File::open(path).expect("input data missing"))
+        .lines()
+        .map(|l| Line::new(&l.expect("read error")));
+    let first = lines.next().expect("No lines");
+    let mut columns = Columns::new(first.len());
+    columns.push(&first);
+    lines.for_each(|l| columns.push(&l));
+    columns
 }
 
-fn most_common_bit(v: &Vec<Line>, pos: usize) -> bool {
-    let mut count = 0;
-    v.iter().for_each(|l| {
-        if l.bool_at_pos(pos) {
-            count += 1;
-        }
-    });
-    count * 2 >= v.len()
+fn gamma_epsilon(v: &Vec<Line>) -> (BigUint, BigUint) {
+    Columns::from_lines(v).gamma_epsilon()
 }
 
-fn least_common_bit(v: &Vec<Line>, pos: usize) -> bool {
+fn most_common_bit(v: &[Line], pos: usize) -> bool {
+    most_common(&symbol_frequencies(v, pos, 2)) == 1
+}
+
+fn least_common_bit(v: &[Line], pos: usize) -> bool {
     !most_common_bit(v, pos)
 }
 
-fn filter_by_position(v: Vec<Line>, pos: usize, byte: bool) -> Vec<Line> {
-    v.into_iter()
-        .filter(|l| l.bool_at_pos(pos) == byte)
-        .collect()
+fn bits_to_decimal(v: &Line) -> BigUint {
+    BigUint::from_limbs(v.bits.clone())
+}
+
+fn filter_by_criterion<F>(v: Vec<Line>, fun: F) -> BigUint
+where
+    F: Fn(&[Line], usize) -> bool,
+{
+    bits_to_decimal(&filter_by_symbol(v, |values, pos| {
+        fun(values, pos) as usize
+    }))
 }
 
-fn bits_to_decimal(v: &Line) -> usize {
-    let mut ret = 0;
-    for x in 0..v.len() {
-        ret += v.value_at_pos(x) << (v.len() - 1 - x);
+// a row of symbols, each one the index of the symbol in its alphabet
+trait Row {
+    fn len(&self) -> usize;
+    fn symbol_at_pos(&self, pos: usize) -> usize;
+}
+
+impl Row for Line {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn symbol_at_pos(&self, pos: usize) -> usize {
+        self.value_at_pos(pos)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Alphabet {
+    symbols: Vec<char>,
+}
+
+impl Alphabet {
+    fn new(symbols: &str) -> Alphabet {
+        let symbols = symbols.chars().collect_vec();
+        assert!(symbols.len() >= 2, "Alphabet is too small");
+        assert!(symbols.iter().all_unique(), "Duplicate symbol");
+        Alphabet { symbols }
+    }
+
+    fn radix(&self) -> usize {
+        self.symbols.len()
     }
-    ret
+
+    fn index(&self, c: char) -> usize {
+        self.symbols
+            .iter()
+            .position(|&s| s == c)
+            .expect("Unexpected symbol")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    symbols: Vec<usize>,
+}
+
+impl Word {
+    fn new(str: &str, alphabet: &Alphabet) -> Word {
+        Word {
+            symbols: str.chars().map(|c| alphabet.index(c)).collect_vec(),
+        }
+    }
+}
+
+impl Row for Word {
+    fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    fn symbol_at_pos(&self, pos: usize) -> usize {
+        self.symbols[pos]
+    }
+}
+
+fn read_file_into_words(path: &str, alphabet: &Alphabet) -> Vec<Word> {
+    utils::read_file_into_vector(path, |l| Word::new(l, alphabet))
+}
+
+fn symbol_frequencies<R: Row>(v: &[R], pos: usize, radix: usize) -> Vec<usize> {
+    let mut frequencies = vec![0; radix];
+    v.iter()
+        .for_each(|r| frequencies[r.symbol_at_pos(pos)] += 1);
+    frequencies
+}
+
+// ties go to the highest symbol for the most common one and to the lowest
+// symbol for the least common one, which is 1 and 0 for binary lines
+fn most_common(frequencies: &[usize]) -> usize {
+    (0..frequencies.len())
+        .max_by_key(|&s| (frequencies[s], s))
+        .unwrap()
+}
+
+fn least_common(frequencies: &[usize]) -> usize {
+    (0..frequencies.len())
+        .min_by_key(|&s| (frequencies[s], s))
+        .unwrap()
+}
+
+// least common among the symbols that occur, so filtering keeps a row
+fn least_common_present(frequencies: &[usize]) -> usize {
+    (0..frequencies.len())
+        .filter(|&s| frequencies[s] > 0)
+        .min_by_key(|&s| (frequencies[s], s))
+        .expect("No rows")
+}
+
+fn row_to_decimal<R: Row>(r: &R, radix: usize) -> BigUint {
+    BigUint::from_digits((0..r.len()).map(|pos| r.symbol_at_pos(pos)), radix)
+}
+
+fn gamma_epsilon_radix<R: Row>(v: &[R], radix: usize) -> (BigUint, BigUint) {
+    let frequencies = (0..v[0].len())
+        .map(|pos| symbol_frequencies(v, pos, radix))
+        .collect_vec();
+    let gamma = frequencies.iter().map(|f| most_common(f));
+    let epsilon = frequencies.iter().map(|f| least_common(f));
+    (
+        BigUint::from_digits(gamma, radix),
+        BigUint::from_digits(epsilon, radix),
+    )
 }
 
-fn filter_by_criterion<F>(v: Vec<Line>, fun: F) -> usize
+fn filter_by_symbol<R, F>(v: Vec<R>, fun: F) -> R
 where
-    F: Fn(&Vec<Line>, usize) -> bool,
+    R: Row,
+    F: Fn(&[R], usize) -> usize,
 {
     let linelen = v[0].len();
     let mut values = v;
     for pos in 0..linelen {
-        let byte = fun(&values, pos);
-        values = filter_by_position(values, pos, byte);
+        let symbol = fun(&values, pos);
+        values.retain(|r| r.symbol_at_pos(pos) == symbol);
         if values.len() == 1 {
             break;
         }
     }
-    bits_to_decimal(&values[0])
+    values.into_iter().next().expect("No row left")
+}
+
+fn ratings_radix<R: Row + Clone>(v: &[R], radix: usize) -> (BigUint, BigUint) {
+    let most = filter_by_symbol(v.to_vec(), |values, pos| {
+        most_common(&symbol_frequencies(values, pos, radix))
+    });
+    let least = filter_by_symbol(v.to_vec(), |values, pos| {
+        least_common_present(&symbol_frequencies(values, pos, radix))
+    });
+    (row_to_decimal(&most, radix), row_to_decimal(&least, radix))
+}
+
+#[derive(Debug, Default, Clone)]
+struct TrieNode {
+    count: usize,
+    children: [Option<usize>; 2],
+}
+
+// lines keyed by their bit prefix, every node counts the lines below it,
+// removed lines only decrement the counts so nodes with count 0 are empty
+struct Trie {
+    nodes: Vec<TrieNode>,
+    len: usize,
+}
+
+impl Trie {
+    fn new(len: usize) -> Trie {
+        Trie {
+            nodes: vec![TrieNode::default()],
+            len,
+        }
+    }
+
+    fn from_lines(v: &[Line]) -> Trie {
+        let mut trie = Trie::new(v[0].len());
+        v.iter().for_each(|l| trie.insert(l));
+        trie
+    }
+
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map(|n| self.nodes[n].count).unwrap_or(0)
+    }
+
+    fn insert(&mut self, line: &Line) {
+        assert_eq!(line.len(), self.len, "Unexpected line length");
+        let mut node = 0;
+        self.nodes[node].count += 1;
+        for pos in 0..self.len {
+            let bit = line.value_at_pos(pos);
+            node = match self.nodes[node].children[bit] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit] = Some(child);
+                    child
+                }
+            };
+            self.nodes[node].count += 1;
+        }
+    }
+
+    // returns false when the line is not in the trie
+    fn remove(&mut self, line: &Line) -> bool {
+        assert_eq!(line.len(), self.len, "Unexpected line length");
+        let mut path = vec![0];
+        for pos in 0..self.len {
+            let child = self.nodes[*path.last().unwrap()].children[line.value_at_pos(pos)];
+            if self.count(child) == 0 {
+                return false;
+            }
+            path.push(child.unwrap());
+        }
+        path.iter().for_each(|&n| self.nodes[n].count -= 1);
+        true
+    }
+
+    fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    // walks down choosing the bit by `fun(pos, zeros, ones)`, once a single
+    // line is left or the chosen side is empty the only remaining path is followed
+    fn select<F>(&self, fun: F) -> Result<Option<Line>, Tie>
+    where
+        F: Fn(usize, usize, usize) -> Result<bool, Tie>,
+    {
+        if self.len() == 0 {
+            return Ok(None);
+        }
+        let mut node = 0;
+        let mut bits = Vec::with_capacity(self.len);
+        for pos in 0..self.len {
+            let [zeros, ones] = self.nodes[node].children.map(|c| self.count(c));
+            let bit = if zeros == 0 || ones == 0 {
+                ones > 0
+            } else {
+                fun(pos, zeros, ones)?
+            };
+            bits.push(bit);
+            node = self.nodes[node].children[bit as usize].unwrap();
+        }
+        Ok(Some(Line::from_bools(bits)))
+    }
+
+    fn oxygen_with(&self, policy: TiePolicy) -> Result<Option<Line>, Tie> {
+        self.select(|pos, zeros, ones| choose_bit(pos, zeros, ones, true, policy))
+    }
+
+    fn co2_with(&self, policy: TiePolicy) -> Result<Option<Line>, Tie> {
+        self.select(|pos, zeros, ones| choose_bit(pos, zeros, ones, false, policy))
+    }
+
+    fn oxygen(&self) -> Option<Line> {
+        self.oxygen_with(TiePolicy::PreferOne).unwrap()
+    }
+
+    fn co2(&self) -> Option<Line> {
+        self.co2_with(TiePolicy::PreferOne).unwrap()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TiePolicy {
+    PreferOne,
+    PreferZero,
+    Error,
+}
+
+// column in which ones and zeros are equally common
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Tie {
+    pos: usize,
+}
+
+// the policy breaks ties of the most common bit and the least common bit is
+// always its inverse, so epsilon is the complement of gamma under any policy,
+// the puzzle uses `PreferOne`
+fn choose_bit(
+    pos: usize,
+    zeros: usize,
+    ones: usize,
+    most_common: bool,
+    policy: TiePolicy,
+) -> Result<bool, Tie> {
+    let most = if zeros != ones {
+        ones > zeros
+    } else {
+        match policy {
+            TiePolicy::PreferOne => true,
+            TiePolicy::PreferZero => false,
+            TiePolicy::Error => return Err(Tie { pos }),
+        }
+    };
+    Ok(most == most_common)
+}
+
+#[derive(Debug)]
+struct Diagnostics {
+    rows: usize,
+    // number of ones per column
+    counts: Vec<usize>,
+    ratios: Vec<f64>,
+    ties: Vec<usize>,
+    // phi coefficient of every pair of columns, None for constant columns
+    correlation: Vec<Vec<Option<f64>>>,
+}
+
+impl Diagnostics {
+    fn from_lines(v: &[Line]) -> Diagnostics {
+        Diagnostics::from_columns(&Columns::from_lines(v))
+    }
+
+    fn from_columns(columns: &Columns) -> Diagnostics {
+        let rows = columns.rows;
+        let width = columns.width();
+        let counts = columns.counts();
+        let ratios = counts.iter().map(|&c| c as f64 / rows as f64).collect_vec();
+        let ties = (0..width)
+            .filter(|&pos| counts[pos] * 2 == rows)
+            .collect_vec();
+        let correlation = (0..width)
+            .map(|i| {
+                (0..width)
+                    .map(|j| {
+                        let (a, b) = (counts[i] as f64, counts[j] as f64);
+                        let n = rows as f64;
+                        let d = (a * (n - a) * b * (n - b)).sqrt();
+                        if d == 0.0 {
+                            None
+                        } else {
+                            Some((n * columns.count_both(i, j) as f64 - a * b) / d)
+                        }
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        Diagnostics {
+            rows,
+            counts,
+            ratios,
+            ties,
+            correlation,
+        }
+    }
+
+    fn common_bits(&self, most_common: bool, policy: TiePolicy) -> Result<Line, Tie> {
+        let bits = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(pos, &ones)| choose_bit(pos, self.rows - ones, ones, most_common, policy))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Line::from_bools(bits))
+    }
+
+    fn gamma(&self, policy: TiePolicy) -> Result<BigUint, Tie> {
+        Ok(bits_to_decimal(&self.common_bits(true, policy)?))
+    }
+
+    fn epsilon(&self, policy: TiePolicy) -> Result<BigUint, Tie> {
+        Ok(bits_to_decimal(&self.common_bits(false, policy)?))
+    }
 }
 
-fn oxygen_co2(v: &Vec<Line>) -> (usize, usize) {
-    let oxygen = filter_by_criterion(v.clone(), most_common_bit);
-    let co2 = filter_by_criterion(v.clone(), least_common_bit);
-    (oxygen, co2)
+fn oxygen(v: &[Line], policy: TiePolicy) -> Result<BigUint, Tie> {
+    let line = Trie::from_lines(v).oxygen_with(policy)?.expect("No lines");
+    Ok(bits_to_decimal(&line))
 }
 
-fn answer(v: (usize, usize)) -> usize {
-    v.0 * v.1
+fn co2(v: &[Line], policy: TiePolicy) -> Result<BigUint, Tie> {
+    let line = Trie::from_lines(v).co2_with(policy)?.expect("No lines");
+    Ok(bits_to_decimal(&line))
+}
+
+fn oxygen_co2(v: &Vec<Line>) -> (BigUint, BigUint) {
+    let trie = Trie::from_lines(v);
+    let oxygen = trie.oxygen().expect("No lines");
+    let co2 = trie.co2().expect("No lines");
+    (bits_to_decimal(&oxygen), bits_to_decimal(&co2))
+}
+
+fn answer(v: (BigUint, BigUint)) -> BigUint {
+    v.0.mul(&v.1)
 }
 
 #[test]
@@ -142,3 +694,180 @@ fn task2_puzzle_bench(b: &mut test::Bencher) {
         task2_puzzle();
     });
 }
+
+#[test]
+fn wide_example() {
+    let values = read_file_into_vector("src/day3/wide.txt");
+    assert_eq!(values[0].len(), 80);
+    let result = answer(gamma_epsilon(&values));
+    assert_eq!(
+        result.to_string(),
+        "301121044944348364000342474654734100414902663750"
+    );
+    let result = answer(oxygen_co2(&values));
+    assert_eq!(
+        result.to_string(),
+        "349787072410101634949892773588832540885998043750"
+    );
+}
+
+#[test]
+fn trie_test() {
+    let values = read_file_into_vector("src/day3/input.txt");
+    let mut trie = Trie::from_lines(&values);
+    assert_eq!(
+        bits_to_decimal(&trie.oxygen().unwrap()),
+        filter_by_criterion(values.clone(), most_common_bit)
+    );
+    assert_eq!(
+        bits_to_decimal(&trie.co2().unwrap()),
+        filter_by_criterion(values.clone(), least_common_bit)
+    );
+    let (removed, kept) = values.split_at(values.len() / 3);
+    assert!(removed.iter().all(|l| trie.remove(l)));
+    assert_eq!(trie.len(), kept.len());
+    assert_eq!(
+        bits_to_decimal(&trie.oxygen().unwrap()),
+        filter_by_criterion(kept.to_vec(), most_common_bit)
+    );
+    assert_eq!(
+        bits_to_decimal(&trie.co2().unwrap()),
+        filter_by_criterion(kept.to_vec(), least_common_bit)
+    );
+    kept.iter().for_each(|l| assert!(trie.remove(l)));
+    assert!(!trie.remove(&kept[0]));
+    assert_eq!(trie.oxygen(), None);
+}
+
+#[test]
+fn diagnostics_example() {
+    let values = read_file_into_vector("src/day3/example.txt");
+    let diagnostics = Diagnostics::from_lines(&values);
+    assert_eq!(diagnostics.counts, vec![7, 5, 8, 7, 5]);
+    assert_eq!(diagnostics.ratios[2], 8.0 / 12.0);
+    assert!(diagnostics.ties.is_empty());
+    assert_eq!(diagnostics.correlation[0][0], Some(1.0));
+    let phi = diagnostics.correlation[1][2].unwrap();
+    assert!((phi + 0.119522860933).abs() < 1e-9);
+    assert_eq!(diagnostics.correlation[1][2], diagnostics.correlation[2][1]);
+    [
+        TiePolicy::PreferOne,
+        TiePolicy::PreferZero,
+        TiePolicy::Error,
+    ]
+    .iter()
+    .for_each(|&policy| {
+        assert_eq!(diagnostics.gamma(policy), Ok(BigUint::from(22)));
+        assert_eq!(diagnostics.epsilon(policy), Ok(BigUint::from(9)));
+    });
+    assert_eq!(oxygen(&values, TiePolicy::PreferOne), Ok(BigUint::from(23)));
+    assert_eq!(
+        oxygen(&values, TiePolicy::PreferZero),
+        Ok(BigUint::from(22))
+    );
+    assert_eq!(oxygen(&values, TiePolicy::Error), Err(Tie { pos: 4 }));
+    assert_eq!(co2(&values, TiePolicy::PreferOne), Ok(BigUint::from(10)));
+    assert_eq!(co2(&values, TiePolicy::PreferZero), Ok(BigUint::from(15)));
+    assert_eq!(co2(&values, TiePolicy::Error), Err(Tie { pos: 2 }));
+    let policy = TiePolicy::PreferOne;
+    let ratings = (
+        oxygen(&values, policy).unwrap(),
+        co2(&values, policy).unwrap(),
+    );
+    assert_eq!(answer(ratings), 230);
+}
+
+#[test]
+fn diagnostics_ties() {
+    let values = ["110", "111", "011", "010"].map(Line::new);
+    let diagnostics = Diagnostics::from_lines(&values);
+    assert_eq!(diagnostics.ties, vec![0, 2]);
+    assert_eq!(diagnostics.correlation[0][2], Some(0.0));
+    assert_eq!(diagnostics.correlation[1][0], None);
+    let rates = |policy| (diagnostics.gamma(policy), diagnostics.epsilon(policy));
+    assert_eq!(
+        rates(TiePolicy::PreferOne),
+        (Ok(BigUint::from(7)), Ok(BigUint::from(0)))
+    );
+    assert_eq!(
+        rates(TiePolicy::PreferZero),
+        (Ok(BigUint::from(2)), Ok(BigUint::from(5)))
+    );
+    assert_eq!(diagnostics.epsilon(TiePolicy::Error), Err(Tie { pos: 0 }));
+    assert_eq!(diagnostics.gamma(TiePolicy::Error), Err(Tie { pos: 0 }));
+}
+
+#[test]
+fn columns_test() {
+    let values = read_file_into_vector("src/day3/input.txt");
+    let columns = read_file_into_columns("src/day3/input.txt");
+    assert_eq!(columns.rows, values.len());
+    (0..columns.width()).for_each(|pos| {
+        assert_eq!(
+            columns.count_ones(pos) * 2 >= columns.rows,
+            most_common_bit(&values, pos)
+        )
+    });
+    let wide = read_file_into_vector("src/day3/wide.txt");
+    let columns = Columns::from_lines(&wide);
+    let gamma = Line::from_bools((0..wide[0].len()).map(|pos| most_common_bit(&wide, pos)));
+    assert_eq!(
+        columns.gamma_epsilon(),
+        (
+            bits_to_decimal(&gamma),
+            bits_to_decimal(&gamma.complement())
+        )
+    );
+    assert_eq!(columns.count_ones(79), 5);
+}
+
+#[test]
+fn columns_large() {
+    let rows = 1 << 20;
+    let mut columns = Columns::new(12);
+    let mut expected = vec![0; 12];
+    let mut seed = 1_u64;
+    (0..rows).for_each(|_| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let bits = seed >> 52;
+        (0..12).for_each(|pos| expected[pos] += (bits >> (11 - pos)) as usize & 1);
+        columns.push(&Line {
+            bits: vec![bits],
+            len: 12,
+        });
+    });
+    assert_eq!(columns.rows, rows);
+    assert_eq!(columns.counts(), expected);
+}
+
+#[test]
+fn radix_binary() {
+    let values = read_file_into_vector("src/day3/example.txt");
+    assert_eq!(gamma_epsilon_radix(&values, 2), gamma_epsilon(&values));
+    assert_eq!(ratings_radix(&values, 2), oxygen_co2(&values));
+    let alphabet = Alphabet::new("01");
+    let words = read_file_into_words("src/day3/example.txt", &alphabet);
+    assert_eq!(answer(gamma_epsilon_radix(&words, alphabet.radix())), 198);
+    assert_eq!(answer(ratings_radix(&words, alphabet.radix())), 230);
+}
+
+#[test]
+fn radix_example() {
+    let alphabet = Alphabet::new("0123");
+    let words = read_file_into_words("src/day3/example_base4.txt", &alphabet);
+    let (gamma, epsilon) = gamma_epsilon_radix(&words, alphabet.radix());
+    assert_eq!((gamma, epsilon), (BigUint::from(2224), BigUint::from(1621)));
+    let (most, least) = ratings_radix(&words, alphabet.radix());
+    assert_eq!((most, least), (BigUint::from(2958), BigUint::from(1211)));
+    let alphabet = Alphabet::new("0123456789abcdef");
+    let words = ["a3f", "a0f", "13c", "a3c", "ff0", "13f"].map(|w| Word::new(w, &alphabet));
+    let (gamma, epsilon) = gamma_epsilon_radix(&words, alphabet.radix());
+    assert_eq!(
+        (gamma, epsilon),
+        (BigUint::from(0xa3f), BigUint::from(0x011))
+    );
+    let (most, least) = ratings_radix(&words, alphabet.radix());
+    assert_eq!((most, least), (BigUint::from(0xa3f), BigUint::from(0xff0)));
+}
@@ -1,62 +1,262 @@
 use itertools::Itertools;
+use std::fmt;
 use std::fs;
-
-const BOARD_SIZE: usize = 5;
+use std::thread;
 
 type Draws = Vec<usize>;
 type IndexMap = std::collections::HashMap<usize, Vec<(usize, usize, usize)>>;
 
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    // index of the board, None for the draws
+    board: Option<usize>,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.board {
+            Some(board) => write!(f, "board {}: {}", board, self.message),
+            None => write!(f, "draws: {}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pattern {
+    Rows,
+    Columns,
+    Diagonals,
+    Corners,
+    X,
+    Blackout,
+    // named set of (x, y) cells
+    Custom(String, Vec<(usize, usize)>),
+}
+
+impl Pattern {
+    // rows of the mask mark the cells with '#', any other character is ignored
+    fn from_mask(name: &str, mask: &[&str]) -> Pattern {
+        let cells = mask
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect_vec();
+        Pattern::Custom(name.to_string(), cells)
+    }
+
+    // every set of cells that wins on its own
+    fn cell_sets(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        let diagonal = (0..size).map(|i| (i, i)).collect_vec();
+        let anti_diagonal = (0..size).map(|i| (size - 1 - i, i)).collect_vec();
+        match self {
+            Pattern::Rows => (0..size)
+                .map(|y| (0..size).map(|x| (x, y)).collect_vec())
+                .collect_vec(),
+            Pattern::Columns => (0..size)
+                .map(|x| (0..size).map(|y| (x, y)).collect_vec())
+                .collect_vec(),
+            Pattern::Diagonals => vec![diagonal, anti_diagonal],
+            Pattern::Corners => vec![vec![
+                (0, 0),
+                (size - 1, 0),
+                (0, size - 1),
+                (size - 1, size - 1),
+            ]],
+            Pattern::X => vec![diagonal
+                .into_iter()
+                .chain(anti_diagonal)
+                .unique()
+                .collect_vec()],
+            Pattern::Blackout => vec![(0..size).cartesian_product(0..size).collect_vec()],
+            Pattern::Custom(_, cells) => vec![cells.iter().copied().unique().collect_vec()],
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Rules {
+    // a board wins with the first pattern in this order that is complete
+    patterns: Vec<Pattern>,
+    // the center of boards with an odd size is marked from the start, boards
+    // with an even size have no center and are left alone
+    free_center: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            patterns: vec![Pattern::Rows, Pattern::Columns],
+            free_center: false,
+        }
+    }
+}
+
+// the cell sets of all patterns for one board size, boards count hits per set
+#[derive(Clone)]
+struct Masks {
+    lengths: Vec<usize>,
+    patterns: Vec<usize>,
+    // indices of the sets containing the cell at y * size + x
+    cells: Vec<Vec<usize>>,
+}
+
+impl Masks {
+    fn new(rules: &Rules, size: usize) -> Masks {
+        let mut masks = Masks {
+            lengths: vec![],
+            patterns: vec![],
+            cells: vec![vec![]; size * size],
+        };
+        rules.patterns.iter().enumerate().for_each(|(p, pattern)| {
+            pattern.cell_sets(size).into_iter().for_each(|set| {
+                let index = masks.lengths.len();
+                masks.lengths.push(set.len());
+                masks.patterns.push(p);
+                set.iter().for_each(|&(x, y)| {
+                    assert!(x < size && y < size, "Pattern does not fit the board");
+                    masks.cells[y * size + x].push(index);
+                });
+            });
+        });
+        masks
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Win {
+    board: usize,
+    // index into the draws
+    draw: usize,
+    score: usize,
+    pattern: Pattern,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    // index into the draws
+    draw: usize,
+    number: usize,
+    board: usize,
+    // (x, y) of the marked cell
+    cell: (usize, usize),
+    // pattern and score when the cell wins the board
+    win: Option<(Pattern, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Standing {
+    board: usize,
+    // None for boards that never win
+    win: Option<Win>,
+}
+
+#[derive(Clone)]
 struct Board {
-    row_hits: [usize; BOARD_SIZE],
-    column_hits: [usize; BOARD_SIZE],
-    rows: [[(usize, bool); BOARD_SIZE]; BOARD_SIZE],
+    size: usize,
+    hits: Vec<usize>,
+    rows: Vec<Vec<(usize, bool)>>,
+    // pattern completed by the free center alone, the board wins with it
+    // on the first draw that marks one of its cells
+    complete: Option<usize>,
     won: bool,
 }
 
+#[derive(Clone)]
 struct Game {
     index: IndexMap,
     draws: Draws,
     boards: Vec<Board>,
+    rules: Rules,
+    masks: Masks,
 }
 
 impl Board {
-    pub fn new(block: &[&str]) -> Board {
-        let mut rows = [[(0, false); BOARD_SIZE]; BOARD_SIZE];
-        block.iter().enumerate().for_each(|(y, &l)| {
-            l.split_ascii_whitespace().enumerate().for_each(|(x, s)| {
-                rows[y][x].0 = s.parse::<usize>().unwrap();
+    // the size is the number of rows, every row must have as many numbers
+    pub fn new(block: &[&str]) -> Result<Board, String> {
+        let size = block.len();
+        let rows = block
+            .iter()
+            .map(|&l| {
+                let row = l
+                    .split_ascii_whitespace()
+                    .map(|s| s.parse::<usize>().map(|v| (v, false)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("{}: {:?}", e, l))?;
+                if row.len() != size {
+                    return Err(format!("expected {} numbers in row {:?}", size, l));
+                }
+                Ok(row)
             })
-        });
-        Board {
-            row_hits: [0; BOARD_SIZE],
-            column_hits: [0; BOARD_SIZE],
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Board {
+            size,
+            hits: vec![],
+            complete: None,
             rows,
             won: false,
-        }
+        })
     }
 
-    pub fn apply(&mut self, x: usize, y: usize) -> Option<usize> {
-        if self.won {
-            return None;
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    fn reset(&mut self, masks: &Masks, free_center: bool) {
+        self.hits = vec![0; masks.lengths.len()];
+        self.rows
+            .iter_mut()
+            .flatten()
+            .for_each(|value| value.1 = false);
+        self.won = false;
+        self.complete = None;
+        if free_center && self.size % 2 == 1 {
+            self.complete = self.mark(self.size / 2, self.size / 2, masks);
         }
-        self.row_hits[y] += 1;
-        self.column_hits[x] += 1;
+    }
+
+    // returns the index of the first pattern completed by this cell
+    fn mark(&mut self, x: usize, y: usize, masks: &Masks) -> Option<usize> {
         self.rows[y][x].1 = true;
-        if self.row_hits[y] == BOARD_SIZE || self.column_hits[x] == BOARD_SIZE {
-            let unmarked: usize = self
-                .rows
-                .iter()
-                .map(|row| {
-                    row.iter()
-                        .filter_map(|value| if !value.1 { Some(value.0) } else { None })
-                        .sum::<usize>()
-                })
-                .sum();
-            self.won = true;
-            Some(unmarked * self.rows[y][x].0)
-        } else {
-            None
+        let mut completed = None;
+        masks.cells[y * self.size + x].iter().for_each(|&set| {
+            self.hits[set] += 1;
+            if self.hits[set] == masks.lengths[set] && completed.is_none() {
+                completed = Some(masks.patterns[set]);
+            }
+        });
+        completed
+    }
+
+    // returns the score and the index of the winning pattern
+    pub fn apply(&mut self, x: usize, y: usize, masks: &Masks) -> Option<(usize, usize)> {
+        if self.won || self.rows[y][x].1 {
+            return None;
         }
+        let pattern = [self.mark(x, y, masks), self.complete]
+            .into_iter()
+            .flatten()
+            .min()?;
+        let unmarked: usize = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .filter_map(|value| if !value.1 { Some(value.0) } else { None })
+                    .sum::<usize>()
+            })
+            .sum();
+        self.won = true;
+        Some((unmarked * self.rows[y][x].0, pattern))
+    }
+
+    pub fn is_marked(&self, x: usize, y: usize) -> bool {
+        self.rows[y][x].1
     }
 
     pub fn won(&self) -> bool {
@@ -66,55 +266,312 @@ impl Board {
 
 impl Game {
     pub fn new(draws: Draws, boards: Vec<Board>) -> Game {
-        let mut index = IndexMap::with_capacity(draws.len());
+        Game::with_rules(draws, boards, Rules::default())
+    }
+
+    pub fn with_rules(draws: Draws, mut boards: Vec<Board>, rules: Rules) -> Game {
+        let size = boards.first().map(|b| b.size()).unwrap_or(0);
+        assert!(
+            boards.iter().all(|b| b.size() == size),
+            "Boards differ in size"
+        );
+        let masks = Masks::new(&rules, size);
         boards
-            .iter()
-            .enumerate()
-            .cartesian_product(0..BOARD_SIZE)
-            .cartesian_product(0..BOARD_SIZE)
-            .for_each(|(((board_index, b), y), x)| {
+            .iter_mut()
+            .for_each(|b| b.reset(&masks, rules.free_center));
+        let mut index = IndexMap::with_capacity(draws.len());
+        boards.iter().enumerate().for_each(|(board_index, b)| {
+            (0..b.size).cartesian_product(0..b.size).for_each(|(y, x)| {
                 index
                     .entry(b.rows[y][x].0)
                     .or_insert(Vec::new())
                     .push((board_index, x, y));
             });
+        });
         Game {
             index,
             draws,
             boards,
+            rules,
+            masks,
         }
     }
     pub fn play(&mut self) -> usize {
-        self._play(0)
+        self.wins().first().expect("No winner").score
     }
     pub fn play2(&mut self) -> usize {
-        self._play(self.boards.len() - 1)
+        self.wins().last().expect("No winner").score
     }
 
-    fn _play(&mut self, skip: usize) -> usize {
-        self.draws
-            .iter()
-            .filter_map(|d| self.index.get(d))
-            .flat_map(|cells| cells.iter())
-            .filter_map(|c| self.boards[c.0].apply(c.1, c.2))
-            .skip(skip)
-            .next()
-            .expect("No winner")
+    // every marked cell in the order of the draws, boards are reset first so
+    // the game can be replayed
+    pub fn events(&mut self) -> Vec<Event> {
+        self.boards
+            .iter_mut()
+            .for_each(|b| b.reset(&self.masks, self.rules.free_center));
+        let mut events = vec![];
+        self.draws.iter().enumerate().for_each(|(draw, &number)| {
+            self.index
+                .get(&number)
+                .into_iter()
+                .flatten()
+                .for_each(|&(board, x, y)| {
+                    if self.boards[board].won() || self.boards[board].is_marked(x, y) {
+                        return;
+                    }
+                    let win = self.boards[board]
+                        .apply(x, y, &self.masks)
+                        .map(|(score, pattern)| (self.rules.patterns[pattern].clone(), score));
+                    events.push(Event {
+                        draw,
+                        number,
+                        board,
+                        cell: (x, y),
+                        win,
+                    });
+                });
+        });
+        events
+    }
+
+    // every board that wins in the order of winning
+    pub fn wins(&mut self) -> Vec<Win> {
+        self.events()
+            .into_iter()
+            .filter_map(|e| {
+                e.win.map(|(pattern, score)| Win {
+                    board: e.board,
+                    draw: e.draw,
+                    score,
+                    pattern,
+                })
+            })
+            .collect_vec()
+    }
+
+    // boards in the order of winning followed by the boards that never win
+    pub fn ranking(&mut self) -> Vec<Standing> {
+        let mut ranked = vec![false; self.boards.len()];
+        let mut standings = self
+            .wins()
+            .into_iter()
+            .map(|w| {
+                ranked[w.board] = true;
+                Standing {
+                    board: w.board,
+                    win: Some(w),
+                }
+            })
+            .collect_vec();
+        standings.extend(
+            (0..self.boards.len())
+                .filter(|&board| !ranked[board])
+                .map(|board| Standing { board, win: None }),
+        );
+        standings
     }
 }
 
-fn read_file_into_game(path: &str) -> Game {
-    let input = fs::read_to_string(path).expect("Missing input data");
+fn error<T>(board: Option<usize>, message: String) -> Result<T, ParseError> {
+    Err(ParseError { board, message })
+}
+
+// all boards must have the size of the first one
+fn parse_game(input: &str) -> Result<Game, ParseError> {
     let lines = input.lines().collect_vec();
-    let mut blocks = lines.split(|&s| s.is_empty()).into_iter();
-    let draws = blocks.next().expect("Missing draws")[0]
-        .split(',')
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect_vec();
+    let mut blocks = lines.split(|&s| s.is_empty()).filter(|b| !b.is_empty());
+    let draws = match blocks.next() {
+        Some(block) => block[0]
+            .split(',')
+            .map(|s| s.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .or_else(|e| error(None, e.to_string()))?,
+        None => return error(None, "missing draws".to_string()),
+    };
     let boards = blocks
-        .map(|board_input| Board::new(board_input))
+        .enumerate()
+        .map(|(i, block)| Board::new(block).or_else(|message| error(Some(i), message)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let size = match boards.first() {
+        Some(b) => b.size(),
+        None => return error(None, "missing boards".to_string()),
+    };
+    if let Some((i, b)) = boards.iter().find_position(|b| b.size() != size) {
+        return error(
+            Some(i),
+            format!("expected size {}, found {}", size, b.size()),
+        );
+    }
+    Ok(Game::new(draws, boards))
+}
+
+fn read_file_into_game(path: &str) -> Game {
+    let input = fs::read_to_string(path).expect("Missing input data");
+    parse_game(&input).unwrap_or_else(|e| panic!("Malformed input, {}", e))
+}
+
+// splitmix64, good enough to shuffle draws without an external crate
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in 0..n, the bias of the multiply shift is negligible here
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    // Fisher-Yates
+    fn shuffle<T>(&mut self, v: &mut [T]) {
+        (1..v.len())
+            .rev()
+            .for_each(|i| v.swap(i, self.below(i + 1)));
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Estimate {
+    probability: f64,
+    // 95% Wilson score interval, conservative for split credit
+    low: f64,
+    high: f64,
+}
+
+impl Estimate {
+    fn new(successes: f64, trials: usize) -> Estimate {
+        const Z: f64 = 1.96;
+        let n = trials as f64;
+        let p = successes / n;
+        let denominator = 1.0 + Z * Z / n;
+        let center = (p + Z * Z / (2.0 * n)) / denominator;
+        let spread = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / denominator;
+        Estimate {
+            probability: p,
+            low: (center - spread).max(0.0),
+            high: (center + spread).min(1.0),
+        }
+    }
+}
+
+// boards that win on the same draw share the credit of that place
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct WinOdds {
+    board: usize,
+    first: Estimate,
+    last: Estimate,
+    // chance to be one of several boards that win first or last together
+    first_tie: Estimate,
+    last_tie: Estimate,
+}
+
+#[derive(Debug, Clone, Default)]
+struct WinCounts {
+    first: Vec<f64>,
+    last: Vec<f64>,
+    first_ties: Vec<usize>,
+    last_ties: Vec<usize>,
+}
+
+impl WinCounts {
+    fn new(boards: usize) -> WinCounts {
+        WinCounts {
+            first: vec![0.0; boards],
+            last: vec![0.0; boards],
+            first_ties: vec![0; boards],
+            last_ties: vec![0; boards],
+        }
+    }
+
+    // splits one place between every board that wins on the draw of `wins[0]`
+    fn credit<'a, I>(credit: &mut [f64], ties: &mut [usize], wins: I)
+    where
+        I: Iterator<Item = &'a Win>,
+    {
+        let mut wins = wins.peekable();
+        let draw = match wins.peek() {
+            Some(w) => w.draw,
+            None => return,
+        };
+        let boards = wins
+            .take_while(|w| w.draw == draw)
+            .map(|w| w.board)
+            .collect_vec();
+        boards.iter().for_each(|&b| {
+            credit[b] += 1.0 / boards.len() as f64;
+            if boards.len() > 1 {
+                ties[b] += 1;
+            }
+        });
+    }
+
+    fn add_trial(&mut self, wins: &[Win]) {
+        WinCounts::credit(&mut self.first, &mut self.first_ties, wins.iter());
+        WinCounts::credit(&mut self.last, &mut self.last_ties, wins.iter().rev());
+    }
+
+    fn merge(&mut self, other: &WinCounts) {
+        (0..self.first.len()).for_each(|b| {
+            self.first[b] += other.first[b];
+            self.last[b] += other.last[b];
+            self.first_ties[b] += other.first_ties[b];
+            self.last_ties[b] += other.last_ties[b];
+        });
+    }
+}
+
+// counts how often every board wins first and last for the trials in
+// `range`, every trial shuffles the draws with its own seed so the result
+// does not depend on the number of threads
+fn count_wins(mut game: Game, range: std::ops::Range<usize>, seed: u64) -> WinCounts {
+    let mut counts = WinCounts::new(game.boards.len());
+    let draws = game.draws.clone();
+    range.for_each(|trial| {
+        let mut random = Random::new(seed ^ Random::new(trial as u64).next_u64());
+        game.draws.clone_from(&draws);
+        random.shuffle(&mut game.draws);
+        counts.add_trial(&game.wins());
+    });
+    counts
+}
+
+// estimates the odds of every board to win first and last when the draws
+// come in a uniformly random order
+fn simulate(game: &Game, trials: usize, threads: usize, seed: u64) -> Vec<WinOdds> {
+    assert!(trials > 0 && threads > 0);
+    let chunk = trials.div_ceil(threads);
+    let handles = (0..trials)
+        .step_by(chunk)
+        .map(|start| {
+            let game = game.clone();
+            let range = start..trials.min(start + chunk);
+            thread::spawn(move || count_wins(game, range, seed))
+        })
         .collect_vec();
-    Game::new(draws, boards)
+    let mut counts = WinCounts::new(game.boards.len());
+    handles.into_iter().for_each(|h| {
+        counts.merge(&h.join().expect("Simulation failed"));
+    });
+    (0..game.boards.len())
+        .map(|board| WinOdds {
+            board,
+            first: Estimate::new(counts.first[board], trials),
+            last: Estimate::new(counts.last[board], trials),
+            first_tie: Estimate::new(counts.first_ties[board] as f64, trials),
+            last_tie: Estimate::new(counts.last_ties[board] as f64, trials),
+        })
+        .collect_vec()
 }
 
 #[test]
@@ -162,3 +619,224 @@ fn task2_puzzle_bench(b: &mut test::Bencher) {
         task2_puzzle();
     });
 }
+
+#[test]
+fn board_size() {
+    let input = "2,5,8,11,13,15\n\n1 2 3\n4 5 6\n7 8 9\n\n10 11 12\n2 13 14\n5 15 8\n";
+    let mut game = parse_game(input).unwrap();
+    assert_eq!(game.boards[0].size(), 3);
+    assert_eq!(game.play(), 240);
+    let mut game = parse_game(input).unwrap();
+    assert_eq!(game.play2(), 540);
+    let input = "1,2\n\n1 2 3\n4 5 6\n7 8 9\n\n1 2\n3 4\n";
+    assert_eq!(
+        parse_game(input).err().map(|e| e.to_string()),
+        Some("board 1: expected size 3, found 2".to_string())
+    );
+    let input = "1,2\n\n1 2 3\n4 5\n7 8 9\n";
+    assert_eq!(parse_game(input).err().map(|e| e.board), Some(Some(0)));
+}
+
+#[test]
+fn win_patterns() {
+    let game = || read_file_into_game("src/day4/example.txt");
+    let wins = |rules: Rules| {
+        let Game { draws, boards, .. } = game();
+        Game::with_rules(draws, boards, rules)
+            .wins()
+            .iter()
+            .map(|w| (w.board, w.draw, w.score))
+            .collect_vec()
+    };
+    let only = |pattern: Pattern| Rules {
+        patterns: vec![pattern],
+        free_center: false,
+    };
+    let winners = game().wins();
+    assert_eq!(winners[0].pattern, Pattern::Rows);
+    assert_eq!(winners[2].pattern, Pattern::Columns);
+    assert_eq!(
+        wins(only(Pattern::Diagonals)),
+        vec![(2, 7, 494), (1, 19, 1496), (0, 23, 76)]
+    );
+    assert_eq!(
+        wins(only(Pattern::Corners)),
+        vec![(2, 9, 3262), (1, 24, 0), (0, 26, 0)]
+    );
+    assert_eq!(
+        wins(only(Pattern::X)),
+        vec![(2, 15, 858), (1, 24, 0), (0, 26, 0)]
+    );
+    assert_eq!(
+        wins(only(Pattern::Blackout)),
+        vec![(1, 24, 0), (2, 25, 0), (0, 26, 0)]
+    );
+    let plus = Pattern::from_mask("plus", &["..#..", "..#..", "#####", "..#..", "..#.."]);
+    assert_eq!(wins(only(plus)), vec![(1, 23, 57), (0, 24, 3), (2, 25, 0)]);
+    let Game { draws, boards, .. } = game();
+    let rules = Rules {
+        patterns: vec![Pattern::Corners, Pattern::Rows, Pattern::Columns],
+        free_center: false,
+    };
+    let patterns = Game::with_rules(draws, boards, rules)
+        .wins()
+        .into_iter()
+        .map(|w| w.pattern)
+        .collect_vec();
+    assert_eq!(
+        patterns,
+        vec![Pattern::Corners, Pattern::Rows, Pattern::Columns]
+    );
+}
+
+#[test]
+fn free_center() {
+    let input = "2,8,11,15\n\n1 2 3\n4 5 6\n7 8 9\n\n10 11 12\n2 13 14\n5 15 8\n";
+    let Game { draws, boards, .. } = parse_game(input).unwrap();
+    let rules = Rules {
+        free_center: true,
+        ..Rules::default()
+    };
+    let wins = Game::with_rules(draws, boards, rules).wins();
+    assert_eq!(
+        wins.iter()
+            .map(|w| (w.board, w.draw, w.score))
+            .collect_vec(),
+        vec![(0, 1, 240), (1, 3, 615)]
+    );
+    assert!(wins.iter().all(|w| w.pattern == Pattern::Columns));
+}
+
+#[test]
+fn event_log() {
+    let mut game = read_file_into_game("src/day4/example.txt");
+    let events = game.events();
+    assert_eq!(
+        events
+            .iter()
+            .take(3)
+            .map(|e| (e.board, e.cell))
+            .collect_vec(),
+        vec![(0, (4, 2)), (1, (2, 2)), (2, (4, 4))]
+    );
+    assert!(events.iter().take(3).all(|e| e.draw == 0 && e.number == 7));
+    let winners = events.iter().filter(|e| e.win.is_some()).collect_vec();
+    assert_eq!(winners.len(), 3);
+    assert_eq!(winners[0].number, 24);
+    assert_eq!(winners[0].win, Some((Pattern::Rows, 4512)));
+    assert_eq!(
+        winners.last().unwrap().win,
+        Some((Pattern::Columns, game.play2()))
+    );
+    assert_eq!(game.play(), 4512);
+    let ranking = game.ranking();
+    assert_eq!(ranking.iter().map(|s| s.board).collect_vec(), vec![2, 0, 1]);
+}
+
+#[test]
+fn ranking_without_winner() {
+    let input = "2,5,8,13\n\n1 2 3\n4 5 6\n7 8 9\n\n10 11 12\n2 13 14\n5 15 8\n\n20 21 22\n23 24 25\n26 27 28\n";
+    let mut game = parse_game(input).unwrap();
+    let ranking = game.ranking();
+    assert_eq!(ranking.len(), 3);
+    assert_eq!(ranking[0].board, 0);
+    assert_eq!(
+        ranking[0].win.as_ref().map(|w| (w.draw, w.score)),
+        Some((2, 240))
+    );
+    assert_eq!(
+        ranking[1],
+        Standing {
+            board: 1,
+            win: None
+        }
+    );
+    assert_eq!(
+        ranking[2],
+        Standing {
+            board: 2,
+            win: None
+        }
+    );
+    let events = game.events();
+    assert_eq!(events.iter().filter(|e| e.board == 1).count(), 4);
+    assert!(events.iter().all(|e| e.board != 2));
+}
+
+#[test]
+fn shuffle_test() {
+    let mut v = (0..100).collect_vec();
+    Random::new(42).shuffle(&mut v);
+    assert_ne!(v, (0..100).collect_vec());
+    let mut w = (0..100).collect_vec();
+    Random::new(42).shuffle(&mut w);
+    assert_eq!(v, w);
+    v.sort_unstable();
+    assert_eq!(v, (0..100).collect_vec());
+}
+
+#[test]
+fn simulate_fair() {
+    let mut game = parse_game("1,2,3,4\n\n1\n\n2\n\n3\n\n4\n").unwrap();
+    let odds = simulate(&game, 4000, 4, 7);
+    odds.iter().for_each(|o| {
+        assert!((o.first.probability - 0.25).abs() < 0.03);
+        assert!((o.last.probability - 0.25).abs() < 0.03);
+        assert!(o.first.low < o.first.probability && o.first.probability < o.first.high);
+    });
+    // the original draws are left alone
+    assert_eq!(game.wins()[0].board, 0);
+}
+
+#[test]
+fn simulate_example() {
+    let game = read_file_into_game("src/day4/example.txt");
+    let odds = simulate(&game, 500, 3, 1);
+    let single = simulate(&game, 500, 1, 1);
+    odds.iter().zip(single.iter()).for_each(|(a, b)| {
+        assert!((a.first.probability - b.first.probability).abs() < 1e-9);
+        assert!((a.last.probability - b.last.probability).abs() < 1e-9);
+    });
+    let total = |f: fn(&WinOdds) -> f64| odds.iter().map(f).sum::<f64>();
+    assert!((total(|o| o.first.probability) - 1.0).abs() < 1e-9);
+    assert!((total(|o| o.last.probability) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn simulate_identical_boards() {
+    let input = fs::read_to_string("src/day4/example.txt").unwrap();
+    let first_board = input.split("\n\n").nth(1).unwrap();
+    let game = parse_game(&format!("{}\n\n{}", input.trim_end(), first_board)).unwrap();
+    let odds = simulate(&game, 400, 2, 3);
+    assert_eq!(odds[0].first, odds[3].first);
+    assert_eq!(odds[0].last, odds[3].last);
+    assert!(odds[0].first.probability > 0.0);
+    // the copies always win together, so each gets at most half a place
+    assert!(odds[0].first_tie.probability >= 2.0 * odds[0].first.probability - 1e-9);
+    assert!(odds[0].last_tie.probability >= 2.0 * odds[0].last.probability - 1e-9);
+}
+
+#[test]
+fn free_center_edge_cases() {
+    let rules = |patterns| Rules {
+        patterns,
+        free_center: true,
+    };
+    let input = "1,4,2,3\n\n1 2\n3 4\n";
+    let Game { draws, boards, .. } = parse_game(input).unwrap();
+    let wins = Game::with_rules(draws, boards, rules(vec![Pattern::Rows])).wins();
+    assert_eq!(
+        wins.iter().map(|w| (w.draw, w.score)).collect_vec(),
+        vec![(2, 6)]
+    );
+    let center = Pattern::from_mask("center", &["...", ".#.", "..."]);
+    let input = "9,8\n\n1 2 3\n4 5 6\n7 8 9\n\n1 2 3\n4 5 6\n7 0 9\n";
+    let Game { draws, boards, .. } = parse_game(input).unwrap();
+    let wins = Game::with_rules(draws, boards, rules(vec![Pattern::Rows, center.clone()])).wins();
+    assert_eq!(
+        wins.iter()
+            .map(|w| (w.board, w.draw, w.score, w.pattern.clone()))
+            .collect_vec(),
+        vec![(0, 0, 31 * 9, center.clone()), (1, 0, 23 * 9, center)]
+    );
+}
@@ -9,7 +9,7 @@ static NEIGHBORS: [(i16, i16); 4] = [
     (1_i16, 0_i16),
 ];
 
-struct Grid {
+pub(crate) struct Grid {
     points: Vec<Vec<u8>>,
     width: i16,
     height: i16,
@@ -69,6 +69,14 @@ impl<'a> Iterator for NeighborCoordinatesIterator<'a> {
 }
 
 impl Grid {
+    pub(crate) fn point(&self, x: i16, y: i16) -> Option<u8> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some(self.point_unsafe(x, y))
+        }
+    }
+
     fn point_unsafe(&self, x: i16, y: i16) -> u8 {
         unsafe {
             self.points
@@ -96,13 +104,8 @@ impl Grid {
     }
 }
 
-fn read_file_into_grid(path: &str) -> Grid {
-    let points = utils::read_file_into_vector(path, |l| {
-        l.chars()
-            .map(|c| c.to_digit(10).unwrap())
-            .map(|x| x as u8)
-            .collect_vec()
-    });
+pub(crate) fn read_file_into_grid(path: &str) -> Grid {
+    let points = utils::read_file_into_grid(path, |c| c.to_digit(10).unwrap() as u8);
     let width = points[0].len();
     let height = points.len();
     Grid {
@@ -11,3 +11,10 @@ where
         .map(fun)
         .collect_vec()
 }
+
+pub fn read_file_into_grid<T, F>(path: &str, mut fun: F) -> Vec<Vec<T>>
+where
+    F: FnMut(char) -> T,
+{
+    read_file_into_vector(path, |l| l.chars().map(&mut fun).collect_vec())
+}